@@ -1,10 +1,30 @@
-use clap::Parser;
+use crate::cli::config::Config;
+use crate::fetch_availability::{
+    parse_crabfit_event_id, parse_when2meet_url, snapshot_when2meet, snapshot_when2meet_http,
+    AvailabilitySource, Cache, CacheMode, CachedSource, ChromeOptions, CrabFitSource, CsvSource,
+    FileSource, JsonSource, MergedSource, SnapshotSource,
+};
+use crate::manage_event::{EventDays, NewEvent, Respondent, SlotRange, WHEN2MEET_BASE_URL};
+use chrono::{NaiveDate, Weekday};
+use chrono_tz::Tz;
+use clap::{ArgGroup, Parser, Subcommand};
+use iana_time_zone::get_timezone;
+use std::path::PathBuf;
 use url::Url;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+#[command(group(
+    ArgGroup::new("input")
+        .required(true)
+        .args(["when2meet_url", "crabfit", "from_html", "from_snapshot", "from_csv", "from_json"])
+))]
 pub struct Args {
-    /// The people required at the meeting. If not provided, assumed to be all people.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// The people required at the meeting, by name or ID. If not provided, assumed to be all people.
     #[arg(short, long, value_parser, num_args = 1..)]
     pub required_people: Vec<String>,
 
@@ -12,11 +32,471 @@ pub struct Args {
     #[arg(short, long, requires("required_people"))]
     pub flexible_naming: bool,
 
-    /// The URL to the when2meet page.
+    /// The URL to the when2meet page, e.g. https://www.when2meet.com/?21240105-aBcDe. Several events are fetched concurrently and merged into one grid.
+    #[arg(short, long, value_parser = parse_when2meet_url, num_args = 1..)]
+    pub when2meet_url: Vec<Url>,
+
+    /// Read a Crab.fit event instead, by link or ID, e.g. https://crab.fit/team-sync-123456
+    #[arg(long, value_name = "EVENT", value_parser = parse_crabfit_event_id)]
+    pub crabfit: Option<Box<str>>,
+
+    /// Fetch the when2meet page over plain HTTP instead of launching headless Chrome.
+    #[arg(short, long, requires("when2meet_url"))]
+    pub no_browser: bool,
+
+    /// Read a when2meet page saved to disk instead of fetching one.
+    #[arg(long)]
+    pub from_html: Option<PathBuf>,
+
+    /// Replay a snapshot written by the snapshot subcommand instead of fetching the event.
+    #[arg(long)]
+    pub from_snapshot: Option<PathBuf>,
+
+    /// Read a CSV grid of a timestamp column and one 1/0 column per person instead of fetching an event.
+    #[arg(long)]
+    pub from_csv: Option<PathBuf>,
+
+    /// Read availability in the JSON schema described in the README instead of fetching an event.
+    #[arg(long)]
+    pub from_json: Option<PathBuf>,
+
+    /// The output file path. If not provided, it will be printed to stdout.
     #[arg(short, long)]
+    pub output_file_path: Option<PathBuf>,
+
+    /// The config file. If not provided, the platform config dir is checked for when3meet/config.toml.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Add someone who is available wherever their exported calendar is free, e.g. --ics Dana=dana.ics
+    #[arg(long, value_name = "NAME=PATH", value_parser = parse_ics_arg)]
+    pub ics: Vec<(String, PathBuf)>,
+
+    /// Add someone who is available wherever their CalDAV calendar is free, e.g. --caldav Dana=https://dav.example.com/dana/calendar/
+    #[arg(long, value_name = "NAME=URL", value_parser = parse_caldav_arg)]
+    pub caldav: Vec<(String, Url)>,
+
+    /// Drop the slots in which any CalDAV calendar is busy, instead of adding its owner as a person.
+    #[arg(long)]
+    pub caldav_veto: bool,
+
+    /// A TOML file mapping each person's canonical name to the other names they respond under.
+    #[arg(long)]
+    pub aliases: Option<PathBuf>,
+
+    /// Fetch the event even if a fresh copy is cached, then update the cache.
+    #[arg(long, requires("when2meet_url"))]
+    pub refresh: bool,
+
+    /// Only use the cached copy of the event, however old, and never fetch it.
+    #[arg(long, requires("when2meet_url"), conflicts_with("refresh"))]
+    pub offline: bool,
+
+    /// How many seconds a cached event is served before it is fetched again. [default: 300]
+    #[arg(long, value_name = "SECS", requires("when2meet_url"))]
+    pub cache_ttl: Option<u64>,
+
+    #[command(flatten)]
+    pub chrome: ChromeArgs,
+}
+
+impl Args {
+    pub fn source(&self, config: &Config) -> Box<dyn AvailabilitySource> {
+        if let Some(event_id) = &self.crabfit {
+            return Box::new(CrabFitSource::new(
+                config.crabfit.api_url(),
+                event_id.clone(),
+            ));
+        }
+
+        if let Some(path) = &self.from_html {
+            return Box::new(FileSource::new(path.clone()));
+        }
+
+        if let Some(path) = &self.from_snapshot {
+            return Box::new(SnapshotSource::new(path.clone()));
+        }
+
+        if let Some(path) = &self.from_csv {
+            return Box::new(CsvSource::new(path.clone()));
+        }
+
+        if let Some(path) = &self.from_json {
+            return Box::new(JsonSource::new(path.clone()));
+        }
+
+        let mut sources: Vec<Box<dyn AvailabilitySource + Send + Sync>> = self
+            .when2meet_url
+            .iter()
+            .map(|url| self.url_source(url, config))
+            .collect();
+
+        match sources.len() {
+            0 => unreachable!("clap requires one of the input arguments"),
+            1 => sources.remove(0),
+            _ => Box::new(MergedSource::new(sources)),
+        }
+    }
+
+    /// The `[caldav.calendars]` config section with the `--caldav` flags added, replacing calendars
+    /// of the same name.
+    pub fn caldav_calendars(&self, config: &Config) -> Vec<(String, Url)> {
+        let mut calendars = config.caldav.calendars.clone();
+        calendars.extend(self.caldav.iter().cloned());
+        calendars.into_iter().collect()
+    }
+
+    fn url_source(&self, url: &Url, config: &Config) -> Box<dyn AvailabilitySource + Send + Sync> {
+        if self.no_browser {
+            return Box::new(CachedSource::new(
+                url.clone(),
+                self.cache(config),
+                self.cache_mode(),
+                snapshot_when2meet_http,
+            ));
+        }
+
+        let options = self.chrome.options(config);
+        Box::new(CachedSource::new(
+            url.clone(),
+            self.cache(config),
+            self.cache_mode(),
+            move |url: &Url| snapshot_when2meet(url, &options),
+        ))
+    }
+
+    fn cache(&self, config: &Config) -> Cache {
+        let ttl = match self.cache_ttl {
+            Some(ttl) => std::time::Duration::from_secs(ttl),
+            None => config.cache.ttl(),
+        };
+
+        Cache::new(config.cache.dir(), ttl)
+    }
+
+    fn cache_mode(&self) -> CacheMode {
+        match (self.refresh, self.offline) {
+            (true, _) => CacheMode::Refresh,
+            (_, true) => CacheMode::Offline,
+            (false, false) => CacheMode::Normal,
+        }
+    }
+}
+
+/// Splits `--ics Dana=dana.ics` into the person's name and their calendar file.
+fn parse_ics_arg(arg: &str) -> Result<(String, PathBuf), String> {
+    match arg.split_once('=') {
+        Some((name, path)) if !name.trim().is_empty() && !path.is_empty() => {
+            Ok((name.trim().to_string(), PathBuf::from(path)))
+        }
+        _ => Err(format!(
+            "expected NAME=PATH, e.g. Dana=dana.ics, but got '{}'",
+            arg
+        )),
+    }
+}
+
+/// Splits `--caldav Dana=https://...` into the person's name and their calendar collection.
+fn parse_caldav_arg(arg: &str) -> Result<(String, Url), String> {
+    match arg.split_once('=') {
+        Some((name, url)) if !name.trim().is_empty() => match Url::parse(url) {
+            Ok(url) => Ok((name.trim().to_string(), url)),
+            Err(error) => Err(format!("invalid calendar URL '{}': {}", url, error)),
+        },
+        _ => Err(format!(
+            "expected NAME=URL, e.g. Dana=https://dav.example.com/dana/calendar/, but got '{}'",
+            arg
+        )),
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Fetch an event and save its raw names, IDs, slot timestamps and availability as JSON.
+    Snapshot(SnapshotArgs),
+    /// Create a new when2meet event and print its link.
+    Create(CreateArgs),
+    /// Sign in to a when2meet event as a participant and fill in their availability.
+    Respond(RespondArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SnapshotArgs {
+    /// The URL to the when2meet page, e.g. https://www.when2meet.com/?21240105-aBcDe
+    #[arg(short, long, value_parser = parse_when2meet_url)]
     pub when2meet_url: Url,
 
-    /// The output file path. If not provided, it will be printed to stdout.
+    /// Fetch the when2meet page over plain HTTP instead of launching headless Chrome.
+    #[arg(short, long)]
+    pub no_browser: bool,
+
+    /// The snapshot file path. If not provided, it will be printed to stdout.
     #[arg(short, long)]
-    pub output_file_path: Option<std::path::PathBuf>,
+    pub output_file_path: Option<PathBuf>,
+
+    /// The config file. If not provided, the platform config dir is checked for when3meet/config.toml.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub chrome: ChromeArgs,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(group(ArgGroup::new("days").required(true).args(["dates", "weekdays"])))]
+pub struct CreateArgs {
+    /// The name of the event.
+    #[arg(short, long)]
+    pub title: String,
+
+    /// The dates people can pick from, e.g. 2023-09-03 2023-09-04
+    #[arg(long, value_name = "DATE", num_args = 1..)]
+    pub dates: Vec<NaiveDate>,
+
+    /// The days of the week people can pick from, without specific dates, e.g. mon wed fri
+    #[arg(long, value_name = "WEEKDAY", num_args = 1..)]
+    pub weekdays: Vec<Weekday>,
+
+    /// The hour each day starts at, from 0 to 23.
+    #[arg(long, value_name = "HOUR", default_value_t = 9)]
+    pub from: u32,
+
+    /// The hour each day ends at, up to 24 for midnight.
+    #[arg(long, value_name = "HOUR", default_value_t = 17)]
+    pub to: u32,
+
+    /// The timezone of the hours, e.g. America/New_York. If not provided, the system timezone is used.
+    #[arg(long)]
+    pub timezone: Option<Tz>,
+
+    #[arg(long, hide = true, default_value = WHEN2MEET_BASE_URL)]
+    pub base_url: Url,
+}
+
+impl CreateArgs {
+    pub fn new_event(&self) -> NewEvent {
+        let days = if self.weekdays.is_empty() {
+            EventDays::Dates(self.dates.clone())
+        } else {
+            EventDays::Weekdays(self.weekdays.clone())
+        };
+
+        NewEvent {
+            title: self.title.clone(),
+            days,
+            earliest_hour: self.from,
+            latest_hour: self.to,
+            timezone: self
+                .timezone
+                .or_else(|| get_timezone().ok()?.parse().ok())
+                .unwrap_or(Tz::UTC),
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+#[command(group(ArgGroup::new("availability").required(true).args(["slots", "ics"])))]
+pub struct RespondArgs {
+    /// The URL to the when2meet page, e.g. https://www.when2meet.com/?21240105-aBcDe
+    #[arg(short, long, value_parser = parse_when2meet_url)]
+    pub when2meet_url: Url,
+
+    /// The name to sign in as. A new name is added to the event.
+    #[arg(short, long)]
+    pub name: String,
+
+    /// The password to sign in with, if the participant has one. Prefer setting it in the environment, as other users can see the flag in the process list.
+    #[arg(long, env = "WHEN3MEET_PASSWORD", hide_env_values = true)]
+    pub password: Option<String>,
+
+    /// The time ranges to mark available in the event's timezone, e.g. "2023-09-03 13:00-14:30" or "mon 9:00-17:00". Every other slot is marked unavailable.
+    #[arg(long, value_name = "RANGE", num_args = 1..)]
+    pub slots: Vec<SlotRange>,
+
+    /// Mark available every slot in which this iCalendar file is free, and every other slot unavailable.
+    #[arg(long, value_name = "PATH")]
+    pub ics: Option<PathBuf>,
+
+    /// The config file. If not provided, the platform config dir is checked for when3meet/config.toml.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub chrome: ChromeArgs,
+}
+
+impl RespondArgs {
+    pub fn respondent(&self) -> Respondent {
+        Respondent {
+            name: self.name.clone(),
+            password: self.password.clone(),
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_help_heading = "Chrome")]
+pub struct ChromeArgs {
+    /// The Chrome or Chromium binary to launch. If not provided, one is searched for on the system.
+    #[arg(long)]
+    pub chrome_path: Option<PathBuf>,
+
+    /// Launch Chrome without its sandbox, needed when running as root, e.g. in Docker.
+    #[arg(long, conflicts_with = "sandbox")]
+    pub no_sandbox: bool,
+
+    /// Launch Chrome with its sandbox, even if the config file turns it off.
+    #[arg(long)]
+    pub sandbox: bool,
+
+    /// The Chrome user data directory. If not provided, a temporary one is used.
+    #[arg(long)]
+    pub user_data_dir: Option<PathBuf>,
+
+    /// The proxy server Chrome connects through, e.g. http://proxy:3128
+    #[arg(long)]
+    pub proxy_server: Option<String>,
+
+    /// Give up fetching the event, across all attempts, after this many seconds. [default: 120]
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<u64>,
+
+    /// The seconds a single navigation or evaluation may take. [default: 20]
+    #[arg(long, value_name = "SECS")]
+    pub step_timeout: Option<u64>,
+
+    /// How many times to retry a fetch that failed for a transient reason. [default: 2]
+    #[arg(long)]
+    pub retries: Option<u32>,
+}
+
+impl ChromeArgs {
+    /// The `[chrome]` config section with these flags applied on top.
+    pub fn options(&self, config: &Config) -> ChromeOptions {
+        config.chrome.clone().merge(ChromeOptions {
+            path: self.chrome_path.clone(),
+            no_sandbox: if self.no_sandbox {
+                Some(true)
+            } else if self.sandbox {
+                Some(false)
+            } else {
+                None
+            },
+            user_data_dir: self.user_data_dir.clone(),
+            proxy_server: self.proxy_server.clone(),
+            timeout_secs: self.timeout,
+            step_timeout_secs: self.step_timeout,
+            retries: self.retries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::args::{parse_caldav_arg, parse_ics_arg, Args, Command};
+    use crate::cli::config::Config;
+    use crate::manage_event::Respondent;
+    use claims::assert_err;
+    use clap::Parser;
+    use std::path::PathBuf;
+    use url::Url;
+
+    #[test]
+    fn test_parse_ics_arg() {
+        assert_eq!(
+            parse_ics_arg("Dana Scully=cal/dana=work.ics").unwrap(),
+            (
+                "Dana Scully".to_string(),
+                PathBuf::from("cal/dana=work.ics")
+            )
+        );
+        assert_err!(parse_ics_arg("dana.ics"));
+        assert_err!(parse_ics_arg("=dana.ics"));
+        assert_err!(parse_ics_arg("Dana="));
+    }
+
+    #[test]
+    fn test_parse_caldav_arg() {
+        assert_eq!(
+            parse_caldav_arg("Dana=https://dav.example.com/dana/calendar/?a=b").unwrap(),
+            (
+                "Dana".to_string(),
+                Url::parse("https://dav.example.com/dana/calendar/?a=b").unwrap()
+            )
+        );
+        assert_err!(parse_caldav_arg("Dana=not a url"));
+        assert_err!(parse_caldav_arg("https://dav.example.com/"));
+    }
+
+    #[test]
+    fn test_caldav_calendars_flags_replace_config() {
+        let mut config = Config::default();
+        config.caldav.calendars.insert(
+            "Dana".to_string(),
+            Url::parse("https://dav.example.com/dana/old/").unwrap(),
+        );
+        config.caldav.calendars.insert(
+            "Muneer".to_string(),
+            Url::parse("https://dav.example.com/muneer/").unwrap(),
+        );
+        let args = Args::parse_from([
+            "when3meet",
+            "--from-html",
+            "event.html",
+            "--caldav",
+            "Dana=https://dav.example.com/dana/new/",
+        ]);
+
+        assert_eq!(
+            args.caldav_calendars(&config),
+            vec![
+                (
+                    "Dana".to_string(),
+                    Url::parse("https://dav.example.com/dana/new/").unwrap()
+                ),
+                (
+                    "Muneer".to_string(),
+                    Url::parse("https://dav.example.com/muneer/").unwrap()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_password_flag_overrides_environment() {
+        let respondent = |password: &[&str]| {
+            let args = Args::parse_from(
+                [
+                    "when3meet",
+                    "respond",
+                    "--when2meet-url",
+                    "https://www.when2meet.com/?21240105-aBcDe",
+                    "--name",
+                    "Dana",
+                    "--slots",
+                    "mon 9:00-17:00",
+                ]
+                .iter()
+                .chain(password),
+            );
+            match args.command {
+                Some(Command::Respond(respond_args)) => respond_args.respondent(),
+                _ => panic!("expected the respond command"),
+            }
+        };
+
+        std::env::set_var("WHEN3MEET_PASSWORD", "from env");
+        let from_env = respondent(&[]);
+        let from_flag = respondent(&["--password", "from flag"]);
+        std::env::remove_var("WHEN3MEET_PASSWORD");
+
+        assert_eq!(
+            from_env,
+            Respondent {
+                name: "Dana".to_string(),
+                password: Some("from env".to_string()),
+            }
+        );
+        assert_eq!(from_flag.password, Some("from flag".to_string()));
+    }
 }
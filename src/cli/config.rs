@@ -0,0 +1,160 @@
+use crate::cli::errors::ConfigError;
+use crate::fetch_availability::{CacheOptions, CalDavOptions, ChromeOptions, CrabFitOptions};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Settings read from `config.toml`. Every section is optional, and CLI flags take precedence.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The alias file used when `--aliases` isn't given.
+    pub aliases: Option<PathBuf>,
+    pub chrome: ChromeOptions,
+    pub cache: CacheOptions,
+    pub caldav: CalDavOptions,
+    pub crabfit: CrabFitOptions,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/when3meet/config.toml` on Linux, and the platform equivalent elsewhere.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("when3meet").join("config.toml"))
+    }
+
+    /// Reads the config at `path`, or at the default path if it exists. A missing default config
+    /// is not an error, a missing explicit one is.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let path = match (path, Self::default_path()) {
+            (Some(path), _) => path.to_path_buf(),
+            (None, Some(path)) if path.exists() => path,
+            (None, _) => return Ok(Self::default()),
+        };
+
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(source) => return Err(ConfigError::FailedToRead { path, source }),
+        };
+
+        match toml::from_str(&contents) {
+            Ok(config) => Ok(config),
+            Err(source) => Err(ConfigError::FailedToParse { path, source }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::config::Config;
+    use crate::cli::errors::ConfigError;
+    use crate::fetch_availability::ChromeOptions;
+    use claims::{assert_err, assert_ok};
+    use std::path::{Path, PathBuf};
+    use url::Url;
+
+    fn write_config(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_chrome_section() {
+        let path = write_config(
+            "when3meet_test_config_chrome.toml",
+            r#"
+            [chrome]
+            path = "/usr/bin/chromium"
+            no_sandbox = true
+            proxy_server = "http://proxy:3128"
+            "#,
+        );
+
+        let config = Config::load(Some(&path));
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_ok!(&config);
+
+        assert_eq!(
+            config.unwrap().chrome,
+            ChromeOptions {
+                path: Some(PathBuf::from("/usr/bin/chromium")),
+                no_sandbox: Some(true),
+                user_data_dir: None,
+                proxy_server: Some("http://proxy:3128".to_string()),
+                ..ChromeOptions::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_caldav_section() {
+        let path = write_config(
+            "when3meet_test_config_caldav.toml",
+            r#"
+            [caldav]
+            username = "garrett"
+
+            [caldav.calendars]
+            Dana = "https://dav.example.com/dana/calendar/"
+            "#,
+        );
+
+        let config = Config::load(Some(&path));
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_ok!(&config);
+
+        let caldav = config.unwrap().caldav;
+
+        assert_eq!(caldav.username.as_deref(), Some("garrett"));
+        assert_eq!(caldav.password, None);
+        assert_eq!(
+            caldav.calendars.get("Dana").map(Url::as_str),
+            Some("https://dav.example.com/dana/calendar/")
+        );
+    }
+
+    #[test]
+    fn test_load_empty() {
+        let path = write_config("when3meet_test_config_empty.toml", "");
+
+        let config = Config::load(Some(&path));
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_load_unknown_key() {
+        let path = write_config(
+            "when3meet_test_config_unknown_key.toml",
+            "[chrome]\nsandbox = false\n",
+        );
+
+        let config = Config::load(Some(&path));
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_err!(&config);
+
+        assert!(matches!(
+            config.unwrap_err(),
+            ConfigError::FailedToParse { .. }
+        ));
+    }
+
+    #[test]
+    fn test_load_missing_explicit_path() {
+        let config = Config::load(Some(Path::new("does/not/exist.toml")));
+
+        assert_err!(&config);
+
+        assert!(matches!(
+            config.unwrap_err(),
+            ConfigError::FailedToRead { path, .. } if path.ends_with("exist.toml")
+        ));
+    }
+}
@@ -0,0 +1,18 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file {path}")]
+    FailedToRead {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Failed to parse config file {path}")]
+    FailedToParse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+}
@@ -0,0 +1,134 @@
+use crate::fetch_availability::errors::AliasError;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Maps the names people respond under to one canonical name per person.
+///
+/// The alias file is a TOML table of canonical names to their variants, e.g.
+/// `Garrett = ["garrett l", "GL"]`. Names are matched ignoring case and surrounding whitespace, and
+/// the canonical name itself always matches.
+#[derive(Debug, Default, PartialEq)]
+pub struct Aliases {
+    canonical_names: HashMap<String, Box<str>>,
+}
+
+impl Aliases {
+    pub fn read(path: &Path) -> Result<Self, AliasError> {
+        let toml = match std::fs::read_to_string(path) {
+            Ok(toml) => toml,
+            Err(source) => {
+                return Err(AliasError::FailedToRead {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+
+        Self::from_toml(&toml)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, AliasError> {
+        let table: BTreeMap<String, Vec<String>> = match toml::from_str(toml) {
+            Ok(table) => table,
+            Err(source) => return Err(AliasError::FailedToParse(source)),
+        };
+
+        let mut canonical_names: HashMap<String, Box<str>> = HashMap::new();
+
+        for (canonical, variants) in &table {
+            for name in std::iter::once(canonical).chain(variants) {
+                match canonical_names.get(&normalize(name)) {
+                    Some(existing) if **existing != **canonical => {
+                        return Err(AliasError::Conflict {
+                            name: name.clone(),
+                            first: existing.to_string(),
+                            second: canonical.clone(),
+                        })
+                    }
+                    _ => {
+                        canonical_names.insert(normalize(name), canonical.clone().into_boxed_str());
+                    }
+                }
+            }
+        }
+
+        Ok(Self { canonical_names })
+    }
+
+    /// The canonical name for `name`, if it is listed in the aliases.
+    pub fn canonical(&self, name: &str) -> Option<&str> {
+        self.canonical_names
+            .get(&normalize(name))
+            .map(|name| &**name)
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::aliases::Aliases;
+    use crate::fetch_availability::errors::AliasError;
+    use claims::{assert_err, assert_ok};
+    use std::path::Path;
+
+    const ALIASES: &str = r#"
+    Garrett = ["garrett l", "GL"]
+    "Brian Smith" = ["Brian"]
+    "#;
+
+    #[test]
+    fn test_canonical() {
+        let aliases = Aliases::from_toml(ALIASES);
+
+        assert_ok!(&aliases);
+
+        let aliases = aliases.unwrap();
+
+        assert_eq!(aliases.canonical("Garrett"), Some("Garrett"));
+        assert_eq!(aliases.canonical("Garrett L"), Some("Garrett"));
+        assert_eq!(aliases.canonical(" gl "), Some("Garrett"));
+        assert_eq!(aliases.canonical("brian"), Some("Brian Smith"));
+        assert_eq!(aliases.canonical("Muneer"), None);
+    }
+
+    #[test]
+    fn test_from_toml_conflict() {
+        let aliases = Aliases::from_toml(
+            r#"
+            Garrett = ["G"]
+            Greg = ["g"]
+            "#,
+        );
+
+        assert_err!(&aliases);
+
+        assert!(matches!(
+            aliases.unwrap_err(),
+            AliasError::Conflict { name, first, second } if name == "g" && first == "Garrett" && second == "Greg"
+        ));
+    }
+
+    #[test]
+    fn test_from_toml_invalid() {
+        let aliases = Aliases::from_toml("Garrett = \"GL\"");
+
+        assert_err!(&aliases);
+
+        assert!(matches!(aliases.unwrap_err(), AliasError::FailedToParse(_)));
+    }
+
+    #[test]
+    fn test_read_missing_file() {
+        let aliases = Aliases::read(Path::new("does/not/exist.toml"));
+
+        assert_err!(&aliases);
+
+        assert!(matches!(
+            aliases.unwrap_err(),
+            AliasError::FailedToRead { path, .. } if path.ends_with("exist.toml")
+        ));
+    }
+}
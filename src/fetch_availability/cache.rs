@@ -0,0 +1,424 @@
+use crate::fetch_availability::errors::{CacheError, ParseWhen2MeetError};
+use crate::fetch_availability::model::Event;
+use crate::fetch_availability::snapshot::{Snapshot, SNAPSHOT_VERSION};
+use crate::fetch_availability::source::AvailabilitySource;
+use chrono::Utc;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+const DEFAULT_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Where fetched events are cached and for how long, set from the `[cache]` config section.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheOptions {
+    /// If not set, `when3meet` in the platform cache dir, e.g. `~/.cache/when3meet` on Linux.
+    pub dir: Option<PathBuf>,
+    pub ttl_secs: Option<u64>,
+}
+
+impl CacheOptions {
+    pub fn dir(&self) -> PathBuf {
+        match &self.dir {
+            Some(dir) => dir.clone(),
+            None => dirs::cache_dir()
+                .unwrap_or_else(std::env::temp_dir)
+                .join("when3meet"),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl_secs.map_or(DEFAULT_TTL, Duration::from_secs)
+    }
+}
+
+/// Snapshots of fetched events, stored as one JSON file per event URL.
+pub struct Cache {
+    pub dir: PathBuf,
+    pub ttl: Duration,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    pub fn path(&self, url: &Url) -> PathBuf {
+        let key: String = format!(
+            "{}_{}",
+            url.host_str().unwrap_or_default(),
+            url.query().unwrap_or_default()
+        )
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '.' => c,
+            _ => '_',
+        })
+        .collect();
+
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// The cached snapshot of `url` regardless of its age, or `None` if it was never cached or was
+    /// written by a different version of the snapshot format.
+    pub fn get(&self, url: &Url) -> Result<Option<Snapshot>, CacheError> {
+        let path = self.path(url);
+
+        let json = match std::fs::read_to_string(&path) {
+            Ok(json) => json,
+            Err(source) if source.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(CacheError::FailedToRead { path, source }),
+        };
+
+        let snapshot: Snapshot = match serde_json::from_str(&json) {
+            Ok(snapshot) => snapshot,
+            Err(source) => return Err(CacheError::FailedToDeserialize { path, source }),
+        };
+
+        if snapshot.version != SNAPSHOT_VERSION {
+            log::warn!(
+                "Ignoring cached copy of {}: snapshot version {} isn't the supported {}",
+                url,
+                snapshot.version,
+                SNAPSHOT_VERSION
+            );
+            return Ok(None);
+        }
+
+        match snapshot.validate() {
+            Ok(()) => Ok(Some(snapshot)),
+            Err(process_result_error) => {
+                log::warn!("Ignoring cached copy of {}: {}", url, process_result_error);
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn put(&self, url: &Url, snapshot: &Snapshot) -> Result<(), CacheError> {
+        let path = self.path(url);
+
+        if let Err(source) = std::fs::create_dir_all(&self.dir) {
+            return Err(CacheError::FailedToWrite { path, source });
+        }
+
+        let json = match serde_json::to_string(snapshot) {
+            Ok(json) => json,
+            Err(source) => return Err(CacheError::FailedToSerialize(source)),
+        };
+
+        match std::fs::write(&path, json) {
+            Ok(()) => Ok(()),
+            Err(source) => Err(CacheError::FailedToWrite { path, source }),
+        }
+    }
+
+    pub fn is_fresh(&self, snapshot: &Snapshot) -> bool {
+        match (Utc::now() - snapshot.fetched_at).to_std() {
+            Ok(age) => age < self.ttl,
+            // Fetched "in the future", i.e. the clock moved back since.
+            Err(_) => true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CacheMode {
+    /// Serve fresh cached events, fetch and cache the rest.
+    #[default]
+    Normal,
+    /// Always fetch, then update the cache.
+    Refresh,
+    /// Never fetch, serve cached events of any age.
+    Offline,
+}
+
+/// Wraps a snapshot fetch with the on-disk cache.
+pub struct CachedSource<F>
+where
+    F: Fn(&Url) -> Result<Snapshot, ParseWhen2MeetError>,
+{
+    pub url: Url,
+    pub cache: Cache,
+    pub mode: CacheMode,
+    pub fetch_snapshot: F,
+}
+
+impl<F> CachedSource<F>
+where
+    F: Fn(&Url) -> Result<Snapshot, ParseWhen2MeetError>,
+{
+    pub fn new(url: Url, cache: Cache, mode: CacheMode, fetch_snapshot: F) -> Self {
+        Self {
+            url,
+            cache,
+            mode,
+            fetch_snapshot,
+        }
+    }
+
+    fn cached(&self) -> Result<Option<Snapshot>, ParseWhen2MeetError> {
+        match (self.cache.get(&self.url), self.mode) {
+            (Ok(snapshot), _) => Ok(snapshot),
+            (Err(cache_error), CacheMode::Offline) => Err(ParseWhen2MeetError::Cache(cache_error)),
+            (Err(cache_error), _) => {
+                log::warn!("Ignoring cached copy of {}: {}", self.url, cache_error);
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl<F> AvailabilitySource for CachedSource<F>
+where
+    F: Fn(&Url) -> Result<Snapshot, ParseWhen2MeetError>,
+{
+    fn fetch(&self) -> Result<Event, ParseWhen2MeetError> {
+        let cached = match self.mode {
+            CacheMode::Refresh => None,
+            CacheMode::Normal | CacheMode::Offline => self.cached()?,
+        };
+
+        let snapshot = match (cached, self.mode) {
+            (Some(snapshot), CacheMode::Offline) => snapshot,
+            (Some(snapshot), _) if self.cache.is_fresh(&snapshot) => {
+                log::info!(
+                    "Using cached copy of {} from {}",
+                    self.url,
+                    snapshot.fetched_at
+                );
+                snapshot
+            }
+            (None, CacheMode::Offline) => {
+                return Err(ParseWhen2MeetError::Cache(CacheError::Missing {
+                    url: self.url.clone(),
+                }))
+            }
+            (_, _) => {
+                let snapshot = (self.fetch_snapshot)(&self.url)?;
+
+                if let Err(cache_error) = self.cache.put(&self.url, &snapshot) {
+                    log::warn!("Failed to cache {}: {}", self.url, cache_error);
+                }
+
+                snapshot
+            }
+        };
+
+        match snapshot.event() {
+            Ok(event) => Ok(event),
+            Err(process_result_error) => {
+                Err(ParseWhen2MeetError::ProcessResult(process_result_error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::cache::{Cache, CacheMode, CachedSource};
+    use crate::fetch_availability::errors::{CacheError, ParseWhen2MeetError};
+    use crate::fetch_availability::snapshot::{Snapshot, SNAPSHOT_VERSION};
+    use crate::fetch_availability::source::AvailabilitySource;
+    use chrono::Utc;
+    use claims::{assert_err, assert_ok};
+    use std::cell::Cell;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use url::Url;
+
+    fn url() -> Url {
+        Url::parse("https://www.when2meet.com/?21240105-aBcDe").unwrap()
+    }
+
+    fn cache(name: &str, ttl: Duration) -> Cache {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        Cache::new(dir, ttl)
+    }
+
+    fn snapshot() -> Snapshot {
+        Snapshot::from_raw(
+            vec!["Muneer".to_string().into_boxed_str()],
+            vec![94102857],
+            vec![
+                "1693746000,0".to_string().into_boxed_str(),
+                "1693746900,1".to_string().into_boxed_str(),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn fetch_counting(
+        fetches: &Cell<u32>,
+    ) -> impl Fn(&Url) -> Result<Snapshot, ParseWhen2MeetError> + '_ {
+        move |_| {
+            fetches.set(fetches.get() + 1);
+            Ok(snapshot())
+        }
+    }
+
+    #[test]
+    fn test_path_is_keyed_by_event() {
+        let cache = Cache::new(PathBuf::from("/cache"), Duration::from_secs(60));
+
+        assert_eq!(
+            cache.path(&url()),
+            PathBuf::from("/cache/www.when2meet.com_21240105-aBcDe.json")
+        );
+    }
+
+    #[test]
+    fn test_get_put_round_trip() {
+        let cache = cache("when3meet_test_cache_round_trip", Duration::from_secs(60));
+
+        assert_eq!(cache.get(&url()).unwrap(), None);
+
+        assert_ok!(cache.put(&url(), &snapshot()));
+
+        let cached = cache.get(&url()).unwrap();
+
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+
+        assert_eq!(cached.unwrap().event(), snapshot().event());
+    }
+
+    #[test]
+    fn test_get_ignores_other_versions() {
+        let cache = cache(
+            "when3meet_test_cache_other_version",
+            Duration::from_secs(60),
+        );
+        let mut snapshot = snapshot();
+        snapshot.version = SNAPSHOT_VERSION + 1;
+
+        assert_ok!(cache.put(&url(), &snapshot));
+
+        let cached = cache.get(&url());
+
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+
+        assert_eq!(cached.unwrap(), None);
+    }
+
+    #[test]
+    fn test_get_ignores_invalid_snapshots() {
+        let cache = cache("when3meet_test_cache_invalid", Duration::from_secs(60));
+        let mut snapshot = snapshot();
+        snapshot.people_ids.clear();
+
+        assert_ok!(cache.put(&url(), &snapshot));
+
+        let cached = cache.get(&url());
+
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+
+        assert_eq!(cached.unwrap(), None);
+    }
+
+    #[test]
+    fn test_cached_source_refetches_other_versions() {
+        let fetches = Cell::new(0);
+        let cache = cache(
+            "when3meet_test_cache_refetch_version",
+            Duration::from_secs(60),
+        );
+        let mut stale_format = snapshot();
+        stale_format.version = SNAPSHOT_VERSION + 1;
+        cache.put(&url(), &stale_format).unwrap();
+        let source = CachedSource::new(url(), cache, CacheMode::Normal, fetch_counting(&fetches));
+
+        assert_ok!(source.fetch());
+
+        std::fs::remove_dir_all(&source.cache.dir).unwrap();
+
+        assert_eq!(fetches.get(), 1);
+    }
+
+    #[test]
+    fn test_is_fresh() {
+        let cache = Cache::new(PathBuf::from("/cache"), Duration::from_secs(60));
+        let mut snapshot = snapshot();
+
+        assert!(cache.is_fresh(&snapshot));
+
+        snapshot.fetched_at = Utc::now() - chrono::Duration::seconds(61);
+
+        assert!(!cache.is_fresh(&snapshot));
+    }
+
+    #[test]
+    fn test_cached_source_serves_fresh_copy() {
+        let fetches = Cell::new(0);
+        let source = CachedSource::new(
+            url(),
+            cache("when3meet_test_cache_fresh", Duration::from_secs(60)),
+            CacheMode::Normal,
+            fetch_counting(&fetches),
+        );
+
+        let first = source.fetch();
+        let second = source.fetch();
+
+        std::fs::remove_dir_all(&source.cache.dir).unwrap();
+
+        assert_eq!(first.unwrap(), second.unwrap());
+        assert_eq!(fetches.get(), 1);
+    }
+
+    #[test]
+    fn test_cached_source_refetches_stale_copy() {
+        let fetches = Cell::new(0);
+        let source = CachedSource::new(
+            url(),
+            cache("when3meet_test_cache_stale", Duration::ZERO),
+            CacheMode::Normal,
+            fetch_counting(&fetches),
+        );
+
+        assert_ok!(source.fetch());
+        assert_ok!(source.fetch());
+
+        std::fs::remove_dir_all(&source.cache.dir).unwrap();
+
+        assert_eq!(fetches.get(), 2);
+    }
+
+    #[test]
+    fn test_cached_source_refresh() {
+        let fetches = Cell::new(0);
+        let cache = cache("when3meet_test_cache_refresh", Duration::from_secs(60));
+        cache.put(&url(), &snapshot()).unwrap();
+        let source = CachedSource::new(url(), cache, CacheMode::Refresh, fetch_counting(&fetches));
+
+        assert_ok!(source.fetch());
+
+        std::fs::remove_dir_all(&source.cache.dir).unwrap();
+
+        assert_eq!(fetches.get(), 1);
+    }
+
+    #[test]
+    fn test_cached_source_offline() {
+        let fetches = Cell::new(0);
+        let cache = cache("when3meet_test_cache_offline", Duration::ZERO);
+        let source = CachedSource::new(url(), cache, CacheMode::Offline, fetch_counting(&fetches));
+
+        let missing = source.fetch();
+
+        source.cache.put(&url(), &snapshot()).unwrap();
+
+        let stale = source.fetch();
+
+        std::fs::remove_dir_all(&source.cache.dir).unwrap();
+
+        assert_err!(&missing);
+        assert!(matches!(
+            missing.unwrap_err(),
+            ParseWhen2MeetError::Cache(CacheError::Missing { .. })
+        ));
+        assert_eq!(stale.unwrap(), snapshot().event().unwrap());
+        assert_eq!(fetches.get(), 0);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::fetch_availability::errors::CalDavError;
+use crate::fetch_availability::ics::Calendar;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use url::Url;
+
+pub const USERNAME_VAR: &str = "WHEN3MEET_CALDAV_USERNAME";
+pub const PASSWORD_VAR: &str = "WHEN3MEET_CALDAV_PASSWORD";
+
+/// The CalDAV calendars to check, set from the `[caldav]` config section.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CalDavOptions {
+    pub username: Option<String>,
+    /// Better set through `WHEN3MEET_CALDAV_PASSWORD` than written to the config file.
+    pub password: Option<String>,
+    /// Each person's name mapped to the URL of their calendar collection.
+    pub calendars: BTreeMap<String, Url>,
+}
+
+impl CalDavOptions {
+    /// The username and password from the environment, or else from the config.
+    pub fn credentials(&self) -> Option<(String, String)> {
+        self.credentials_from(|name| std::env::var(name).ok())
+    }
+
+    fn credentials_from(&self, var: impl Fn(&str) -> Option<String>) -> Option<(String, String)> {
+        let username = var(USERNAME_VAR).or_else(|| self.username.clone())?;
+        let password = var(PASSWORD_VAR)
+            .or_else(|| self.password.clone())
+            .unwrap_or_default();
+
+        Some((username, password))
+    }
+}
+
+/// Asks CalDAV servers when calendars are busy, with a free-busy-query REPORT (RFC 4791).
+pub struct CalDavClient {
+    credentials: Option<(String, String)>,
+}
+
+impl CalDavClient {
+    pub fn new(credentials: Option<(String, String)>) -> Self {
+        Self { credentials }
+    }
+
+    /// The busy periods of the calendar collection at `url` between `from` and `to`.
+    pub fn free_busy(
+        &self,
+        url: &Url,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Calendar, CalDavError> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+<C:free-busy-query xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <C:time-range start="{}" end="{}"/>
+</C:free-busy-query>
+"#,
+            from.format("%Y%m%dT%H%M%SZ"),
+            to.format("%Y%m%dT%H%M%SZ")
+        );
+
+        let mut request = ureq::request("REPORT", url.as_str())
+            .set("Depth", "1")
+            .set("Content-Type", "application/xml; charset=utf-8");
+
+        if let Some((username, password)) = &self.credentials {
+            let token = STANDARD.encode(format!("{}:{}", username, password));
+            request = request.set("Authorization", &format!("Basic {}", token));
+        }
+
+        let response = match request.send_string(&body) {
+            Ok(response) => response,
+            Err(ureq::Error::Status(401 | 403, _)) => {
+                return Err(CalDavError::Unauthorized { url: url.clone() })
+            }
+            Err(source) => {
+                return Err(CalDavError::FailedRequest {
+                    url: url.clone(),
+                    source: Box::new(source),
+                })
+            }
+        };
+
+        let ics = match response.into_string() {
+            Ok(ics) => ics,
+            Err(source) => {
+                return Err(CalDavError::FailedToReadBody {
+                    url: url.clone(),
+                    source,
+                })
+            }
+        };
+
+        match Calendar::parse(&ics) {
+            Ok(calendar) => Ok(calendar),
+            Err(source) => Err(CalDavError::InvalidResponse {
+                url: url.clone(),
+                source: Box::new(source),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::caldav::{
+        CalDavClient, CalDavOptions, PASSWORD_VAR, USERNAME_VAR,
+    };
+    use crate::fetch_availability::errors::CalDavError;
+    use chrono::{DateTime, Utc};
+    use chrono_tz::Tz;
+    use claims::{assert_err, assert_ok};
+    use std::thread;
+    use url::Url;
+
+    const FREE_BUSY: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VFREEBUSY\r\nDTSTART:20230903T000000Z\r\nDTEND:20230904T000000Z\r\nFREEBUSY:20230903T131500Z/PT15M\r\nEND:VFREEBUSY\r\nEND:VCALENDAR\r\n";
+
+    fn time(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_free_busy() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/dana/calendar/", server.server_addr())).unwrap();
+
+        let handle = thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let header = |name: &'static str| {
+                request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv(name))
+                    .map(|header| header.value.to_string())
+            };
+            let seen = (
+                request.method().to_string(),
+                request.url().to_string(),
+                header("Depth"),
+                header("Authorization"),
+                body,
+            );
+            request
+                .respond(tiny_http::Response::from_string(FREE_BUSY))
+                .unwrap();
+            seen
+        });
+
+        let calendar = CalDavClient::new(Some(("dana".to_string(), "secret".to_string())))
+            .free_busy(
+                &url,
+                time("2023-09-03T13:00:00Z"),
+                time("2023-09-03T14:00:00Z"),
+            );
+
+        let (method, path, depth, authorization, body) = handle.join().unwrap();
+
+        assert_ok!(&calendar);
+
+        assert_eq!(method, "REPORT");
+        assert_eq!(path, "/dana/calendar/");
+        assert_eq!(depth.as_deref(), Some("1"));
+        assert_eq!(authorization.as_deref(), Some("Basic ZGFuYTpzZWNyZXQ="));
+        assert!(body.contains(r#"<C:time-range start="20230903T130000Z" end="20230903T140000Z"/>"#));
+        assert_eq!(
+            calendar.unwrap().busy(
+                time("2023-09-03T13:00:00Z"),
+                time("2023-09-03T14:00:00Z"),
+                Tz::UTC
+            ),
+            vec![(time("2023-09-03T13:15:00Z"), time("2023-09-03T13:30:00Z"))]
+        );
+    }
+
+    #[test]
+    fn test_free_busy_unauthorized() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/dana/calendar/", server.server_addr())).unwrap();
+
+        let handle = thread::spawn(move || {
+            let request = server.recv().unwrap();
+            request
+                .respond(tiny_http::Response::from_string("Unauthorized").with_status_code(401))
+                .unwrap();
+        });
+
+        let calendar = CalDavClient::new(None).free_busy(
+            &url,
+            time("2023-09-03T13:00:00Z"),
+            time("2023-09-03T14:00:00Z"),
+        );
+
+        handle.join().unwrap();
+
+        assert_err!(&calendar);
+
+        assert!(matches!(
+            calendar.unwrap_err(),
+            CalDavError::Unauthorized { url: unauthorized_url } if unauthorized_url == url
+        ));
+    }
+
+    #[test]
+    fn test_credentials_prefer_environment() {
+        let options = CalDavOptions {
+            username: Some("config".to_string()),
+            password: Some("from config".to_string()),
+            ..CalDavOptions::default()
+        };
+
+        assert_eq!(
+            options.credentials_from(|_| None),
+            Some(("config".to_string(), "from config".to_string()))
+        );
+        assert_eq!(
+            options.credentials_from(|name| match name {
+                PASSWORD_VAR => Some("from env".to_string()),
+                _ => None,
+            }),
+            Some(("config".to_string(), "from env".to_string()))
+        );
+        assert_eq!(
+            CalDavOptions::default().credentials_from(|name| match name {
+                USERNAME_VAR => Some("env".to_string()),
+                _ => None,
+            }),
+            Some(("env".to_string(), String::new()))
+        );
+        assert_eq!(CalDavOptions::default().credentials_from(|_| None), None);
+    }
+}
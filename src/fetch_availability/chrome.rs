@@ -0,0 +1,165 @@
+use crate::fetch_availability::retry::{Deadline, RetryPolicy};
+use headless_chrome::LaunchOptions;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How headless Chrome is launched, set from the `[chrome]` config section and the CLI flags.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChromeOptions {
+    /// The Chrome or Chromium binary. If not set, one is searched for on the system.
+    pub path: Option<PathBuf>,
+    /// Needed when running as root, e.g. inside most Docker containers. Unset means sandboxed.
+    pub no_sandbox: Option<bool>,
+    pub user_data_dir: Option<PathBuf>,
+    pub proxy_server: Option<String>,
+    /// How long, across all attempts, fetching the event may take.
+    pub timeout_secs: Option<u64>,
+    /// How long a single navigation or evaluation may take.
+    pub step_timeout_secs: Option<u64>,
+    /// How many times to retry a fetch that failed for a transient reason.
+    pub retries: Option<u32>,
+}
+
+impl ChromeOptions {
+    /// Options set in `overrides` take precedence over the ones in `self`.
+    pub fn merge(self, overrides: ChromeOptions) -> Self {
+        Self {
+            path: overrides.path.or(self.path),
+            no_sandbox: overrides.no_sandbox.or(self.no_sandbox),
+            user_data_dir: overrides.user_data_dir.or(self.user_data_dir),
+            proxy_server: overrides.proxy_server.or(self.proxy_server),
+            timeout_secs: overrides.timeout_secs.or(self.timeout_secs),
+            step_timeout_secs: overrides.step_timeout_secs.or(self.step_timeout_secs),
+            retries: overrides.retries.or(self.retries),
+        }
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        let default = RetryPolicy::default();
+
+        RetryPolicy {
+            retries: self.retries.unwrap_or(default.retries),
+            timeout: self
+                .timeout_secs
+                .map_or(default.timeout, Duration::from_secs),
+            step_timeout: self
+                .step_timeout_secs
+                .map_or(default.step_timeout, Duration::from_secs),
+            initial_backoff: default.initial_backoff,
+        }
+    }
+
+    /// The deadline of a single attempt that isn't retried, e.g. responding to an event.
+    pub fn deadline(&self) -> Deadline {
+        let policy = self.retry_policy();
+
+        Deadline::new(policy.timeout, policy.step_timeout)
+    }
+
+    pub fn launch_options(&self) -> LaunchOptions<'_> {
+        LaunchOptions {
+            path: self.path.clone(),
+            sandbox: !self.no_sandbox.unwrap_or(false),
+            user_data_dir: self.user_data_dir.clone(),
+            proxy_server: self.proxy_server.as_deref(),
+            // Otherwise Chrome is torn down while a long step is still waiting on it.
+            idle_browser_timeout: self.retry_policy().timeout,
+            ..LaunchOptions::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::chrome::ChromeOptions;
+    use crate::fetch_availability::retry::RetryPolicy;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    #[test]
+    fn test_launch_options_default() {
+        let options = ChromeOptions::default();
+
+        let launch_options = options.launch_options();
+
+        assert!(launch_options.sandbox);
+        assert!(launch_options.headless);
+        assert_eq!(launch_options.path, None);
+        assert_eq!(launch_options.proxy_server, None);
+    }
+
+    #[test]
+    fn test_launch_options() {
+        let options = ChromeOptions {
+            path: Some(PathBuf::from("/usr/bin/chromium")),
+            no_sandbox: Some(true),
+            user_data_dir: Some(PathBuf::from("/tmp/when3meet")),
+            proxy_server: Some("http://proxy:3128".to_string()),
+            ..ChromeOptions::default()
+        };
+
+        let launch_options = options.launch_options();
+
+        assert!(!launch_options.sandbox);
+        assert_eq!(launch_options.path, options.path);
+        assert_eq!(launch_options.user_data_dir, options.user_data_dir);
+        assert_eq!(launch_options.proxy_server, Some("http://proxy:3128"));
+    }
+
+    #[test]
+    fn test_merge_prefers_overrides() {
+        let config = ChromeOptions {
+            path: Some(PathBuf::from("/usr/bin/chromium")),
+            no_sandbox: Some(true),
+            user_data_dir: None,
+            proxy_server: Some("http://proxy:3128".to_string()),
+            retries: Some(5),
+            ..ChromeOptions::default()
+        };
+        let flags = ChromeOptions {
+            path: Some(PathBuf::from("/opt/chrome/chrome")),
+            retries: Some(0),
+            ..ChromeOptions::default()
+        };
+        let sandbox = ChromeOptions {
+            no_sandbox: Some(false),
+            ..ChromeOptions::default()
+        };
+
+        assert_eq!(config.clone().merge(sandbox).no_sandbox, Some(false));
+
+        assert_eq!(
+            config.merge(flags),
+            ChromeOptions {
+                path: Some(PathBuf::from("/opt/chrome/chrome")),
+                no_sandbox: Some(true),
+                user_data_dir: None,
+                proxy_server: Some("http://proxy:3128".to_string()),
+                retries: Some(0),
+                ..ChromeOptions::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        let options = ChromeOptions {
+            timeout_secs: Some(60),
+            step_timeout_secs: Some(5),
+            retries: Some(4),
+            ..ChromeOptions::default()
+        };
+
+        let policy = options.retry_policy();
+
+        assert_eq!(policy.retries, 4);
+        assert_eq!(policy.timeout, Duration::from_secs(60));
+        assert_eq!(policy.step_timeout, Duration::from_secs(5));
+        assert_eq!(
+            ChromeOptions::default().retry_policy(),
+            RetryPolicy::default()
+        );
+    }
+}
@@ -0,0 +1,392 @@
+use crate::fetch_availability::errors::{CrabFitError, HttpError, ParseWhen2MeetError};
+use crate::fetch_availability::model::{Event, EventMetadata, Person};
+use crate::fetch_availability::source::AvailabilitySource;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use url::Url;
+
+const DEFAULT_API_URL: &str = "https://api.crab.fit";
+
+/// Where the Crab.fit API is, set from the `[crabfit]` config section for self-hosted instances.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrabFitOptions {
+    pub api_url: Option<Url>,
+}
+
+impl CrabFitOptions {
+    pub fn api_url(&self) -> Url {
+        match &self.api_url {
+            Some(api_url) => api_url.clone(),
+            None => Url::parse(DEFAULT_API_URL).expect("the default API URL is valid"),
+        }
+    }
+}
+
+/// A Crab.fit event, as returned by `GET /event/{id}`.
+#[derive(Debug, Deserialize)]
+struct CrabFitEvent {
+    name: Option<Box<str>>,
+    timezone: Option<Box<str>>,
+    /// Every slot of the event, in UTC.
+    times: Vec<Box<str>>,
+}
+
+/// A respondent, as returned by `GET /event/{id}/people`.
+#[derive(Debug, Deserialize)]
+struct CrabFitPerson {
+    name: Box<str>,
+    /// The slots the person is available in, in the format of the event's `times`.
+    availability: Vec<Box<str>>,
+}
+
+/// Reads an event from the JSON API of Crab.fit, or of a self-hosted instance.
+pub struct CrabFitSource {
+    pub api_url: Url,
+    pub event_id: Box<str>,
+}
+
+impl CrabFitSource {
+    pub fn new(api_url: Url, event_id: Box<str>) -> Self {
+        Self { api_url, event_id }
+    }
+
+    fn endpoint(&self, path: &str) -> Result<Url, ParseWhen2MeetError> {
+        let endpoint = format!(
+            "{}/event/{}{}",
+            self.api_url.as_str().trim_end_matches('/'),
+            self.event_id,
+            path
+        );
+
+        match Url::parse(&endpoint) {
+            Ok(url) => Ok(url),
+            Err(_) => Err(ParseWhen2MeetError::CrabFit(CrabFitError::InvalidEvent {
+                event: self.event_id.to_string(),
+            })),
+        }
+    }
+}
+
+impl AvailabilitySource for CrabFitSource {
+    fn fetch(&self) -> Result<Event, ParseWhen2MeetError> {
+        let event: CrabFitEvent = fetch_json(&self.endpoint("")?)?;
+        let people: Vec<CrabFitPerson> = fetch_json(&self.endpoint("/people")?)?;
+
+        crabfit_event(event, people)
+    }
+}
+
+/// The ID of a Crab.fit event, given either the ID itself or a link such as
+/// `https://crab.fit/team-sync-123456`.
+pub fn parse_crabfit_event_id(event: &str) -> Result<Box<str>, CrabFitError> {
+    let id = match Url::parse(event) {
+        Ok(url) => match url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+        {
+            Some(id) => id.to_string(),
+            None => String::new(),
+        },
+        Err(_) => event.to_string(),
+    };
+
+    if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        Ok(id.into_boxed_str())
+    } else {
+        Err(CrabFitError::InvalidEvent {
+            event: event.to_string(),
+        })
+    }
+}
+
+fn fetch_json<T: DeserializeOwned>(url: &Url) -> Result<T, ParseWhen2MeetError> {
+    let response = match ureq::get(url.as_str()).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => {
+            return Err(ParseWhen2MeetError::EventNotFound { url: url.clone() })
+        }
+        Err(source) => {
+            return Err(ParseWhen2MeetError::Http(HttpError::FailedRequest {
+                url: url.clone(),
+                source: Box::new(source),
+            }))
+        }
+    };
+
+    let json = match response.into_string() {
+        Ok(json) => json,
+        Err(source) => {
+            return Err(ParseWhen2MeetError::Http(HttpError::FailedToReadBody {
+                url: url.clone(),
+                source,
+            }))
+        }
+    };
+
+    match serde_json::from_str(&json) {
+        Ok(value) => Ok(value),
+        Err(source) => Err(ParseWhen2MeetError::CrabFit(
+            CrabFitError::FailedToDeserialize {
+                url: url.clone(),
+                source,
+            },
+        )),
+    }
+}
+
+fn crabfit_event(
+    event: CrabFitEvent,
+    people: Vec<CrabFitPerson>,
+) -> Result<Event, ParseWhen2MeetError> {
+    let timezone: Option<Tz> = event
+        .timezone
+        .as_deref()
+        .and_then(|timezone| timezone.parse().ok());
+
+    let mut times = Vec::with_capacity(event.times.len());
+    for time in &event.times {
+        times.push((parse_crabfit_time(time, timezone)?, time));
+    }
+    times.sort();
+
+    let rows = times
+        .into_iter()
+        .map(|(start_time, time)| {
+            let people = people
+                .iter()
+                .map(|person| Person {
+                    id: person.name.clone(),
+                    name: person.name.clone(),
+                    available: person.availability.contains(time),
+                })
+                .collect();
+
+            (start_time, people)
+        })
+        .collect();
+
+    Ok(Event::new(rows).with_metadata(EventMetadata {
+        title: event.name,
+        timezone: event.timezone,
+    }))
+}
+
+/// Parses a slot of a specific dates event, `HHmm-DDMMYYYY`, or of a days of the week event,
+/// `HHmm-d` with 0 for Sunday. Both are in UTC; days of the week slots are moved to when2meet's
+/// reference week at the time of day they have in `timezone`.
+pub fn parse_crabfit_time(
+    time: &str,
+    timezone: Option<Tz>,
+) -> Result<DateTime<Utc>, ParseWhen2MeetError> {
+    let invalid = || {
+        ParseWhen2MeetError::CrabFit(CrabFitError::InvalidTime {
+            time: time.to_string(),
+        })
+    };
+
+    let (time_of_day, day) = match time.split_once('-') {
+        Some(parts) => parts,
+        None => return Err(invalid()),
+    };
+
+    let time_of_day = match NaiveTime::parse_from_str(time_of_day, "%H%M") {
+        Ok(time_of_day) => time_of_day,
+        Err(_) => return Err(invalid()),
+    };
+
+    if let Ok(date) = NaiveDate::parse_from_str(day, "%d%m%Y") {
+        return Ok(Utc.from_utc_datetime(&date.and_time(time_of_day)));
+    }
+
+    let weekday: i64 = match day.parse() {
+        Ok(weekday @ 0..=6) => weekday,
+        _ => return Err(invalid()),
+    };
+
+    // Sunday, January 4th 1970, starts when2meet's reference week.
+    let sunday = NaiveDate::from_ymd_opt(1970, 1, 4).expect("a valid date");
+    let utc = NaiveDateTime::new(sunday + Duration::days(weekday), time_of_day);
+    let offset = match timezone {
+        Some(timezone) => timezone
+            .offset_from_utc_datetime(&Utc::now().naive_utc())
+            .fix()
+            .local_minus_utc(),
+        None => 0,
+    };
+    // Keep the slot within the reference week when the offset moves it across Sunday midnight.
+    let local = sunday.and_time(NaiveTime::MIN)
+        + Duration::seconds(
+            (utc - sunday.and_time(NaiveTime::MIN) + Duration::seconds(offset.into()))
+                .num_seconds()
+                .rem_euclid(Duration::weeks(1).num_seconds()),
+        );
+
+    Ok(Utc.from_utc_datetime(&local))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::crabfit::{
+        parse_crabfit_event_id, parse_crabfit_time, CrabFitSource,
+    };
+    use crate::fetch_availability::errors::{CrabFitError, ParseWhen2MeetError};
+    use crate::fetch_availability::model::EventMode;
+    use crate::fetch_availability::source::AvailabilitySource;
+    use chrono::{DateTime, Utc};
+    use claims::{assert_err, assert_ok};
+    use std::thread;
+    use url::Url;
+
+    const EVENT: &str = r#"{
+        "id": "team-sync-123456",
+        "name": "Team Sync",
+        "timezone": "America/New_York",
+        "created_at": 1693700000,
+        "times": ["1315-03092023", "1300-03092023", "1330-03092023", "1345-03092023"]
+    }"#;
+
+    const PEOPLE: &str = r#"[
+        {"name": "Muneer", "availability": ["1315-03092023", "1330-03092023", "1345-03092023"], "created_at": 1693700100},
+        {"name": "Brian", "availability": ["1330-03092023", "1345-03092023"], "created_at": 1693700200},
+        {"name": "Garrett", "availability": ["1345-03092023"], "created_at": 1693700300}
+    ]"#;
+
+    fn time(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    /// Serves `responses` in order, one per request, and returns the requested paths.
+    fn serve(responses: Vec<(u16, &'static str)>) -> (Url, thread::JoinHandle<Vec<String>>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let api_url = Url::parse(&format!("http://{}/", server.server_addr())).unwrap();
+
+        let handle = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|(status, body)| {
+                    let request = server.recv().unwrap();
+                    let path = request.url().to_string();
+                    request
+                        .respond(tiny_http::Response::from_string(body).with_status_code(status))
+                        .unwrap();
+                    path
+                })
+                .collect()
+        });
+
+        (api_url, handle)
+    }
+
+    #[test]
+    fn test_crabfit_source() {
+        let (api_url, handle) = serve(vec![(200, EVENT), (200, PEOPLE)]);
+
+        let event = CrabFitSource::new(api_url, "team-sync-123456".into()).fetch();
+
+        let paths = handle.join().unwrap();
+
+        assert_ok!(&event);
+
+        let event = event.unwrap();
+
+        assert_eq!(
+            paths,
+            vec!["/event/team-sync-123456", "/event/team-sync-123456/people"]
+        );
+        assert_eq!(event.title.as_deref(), Some("Team Sync"));
+        assert_eq!(event.participants.len(), 3);
+        assert_eq!(
+            event
+                .slots
+                .iter()
+                .map(|slot| (
+                    slot.start_time,
+                    slot.people.iter().filter(|person| person.available).count()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (time("2023-09-03T13:00:00Z"), 0),
+                (time("2023-09-03T13:15:00Z"), 1),
+                (time("2023-09-03T13:30:00Z"), 2),
+                (time("2023-09-03T13:45:00Z"), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_crabfit_source_event_not_found() {
+        let (api_url, handle) = serve(vec![(404, r#"{"error": "Event not found"}"#)]);
+
+        let event = CrabFitSource::new(api_url, "deleted-123456".into()).fetch();
+
+        handle.join().unwrap();
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::EventNotFound { url } if url.path() == "/event/deleted-123456"
+        ));
+    }
+
+    #[test]
+    fn test_crabfit_source_unexpected_json() {
+        let (api_url, handle) = serve(vec![(200, r#"{"error": "Rate limited"}"#)]);
+
+        let event = CrabFitSource::new(api_url, "team-sync-123456".into()).fetch();
+
+        handle.join().unwrap();
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::CrabFit(CrabFitError::FailedToDeserialize { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_crabfit_time_days_of_the_week() {
+        // Mondays at 14:00 UTC.
+        let start_time = parse_crabfit_time("1400-1", None);
+
+        assert_ok!(&start_time);
+
+        assert_eq!(start_time.unwrap(), time("1970-01-05T14:00:00Z"));
+
+        // Sundays at 00:30 UTC are Saturdays at 19:30 or 20:30 in New York.
+        let start_time = parse_crabfit_time("0030-0", "America/New_York".parse().ok()).unwrap();
+
+        assert_eq!(start_time.date_naive().to_string(), "1970-01-10");
+        assert_eq!(EventMode::detect(&[start_time]), EventMode::DaysOfTheWeek);
+    }
+
+    #[test]
+    fn test_parse_crabfit_time_invalid() {
+        for time in ["", "1400", "2500-03092023", "1400-7", "1400-32092023"] {
+            assert!(matches!(
+                parse_crabfit_time(time, None).unwrap_err(),
+                ParseWhen2MeetError::CrabFit(CrabFitError::InvalidTime { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_crabfit_event_id() {
+        assert_eq!(
+            parse_crabfit_event_id("https://crab.fit/team-sync-123456").unwrap(),
+            "team-sync-123456".into()
+        );
+        assert_eq!(
+            parse_crabfit_event_id("team-sync-123456").unwrap(),
+            "team-sync-123456".into()
+        );
+        assert_err!(parse_crabfit_event_id("https://crab.fit/"));
+        assert_err!(parse_crabfit_event_id("team sync"));
+    }
+}
@@ -1,17 +1,41 @@
+use std::path::PathBuf;
 use thiserror::Error;
+use url::Url;
 
 #[derive(Error, Debug)]
 pub enum ParseError {
     #[error("Raw names string is empty, cannot parse")]
     EmptyRaw,
+    #[error("Failed to parse person ID: {id}")]
+    FailedIdParse { id: String },
+    #[error("Failed to parse `{entry}` on the event page")]
+    FailedEntryParse { entry: Box<str> },
+    #[error("Failed to parse people names")]
+    FailedNamesParse(#[source] serde_json::Error),
+    #[error("Failed to parse page readiness")]
+    FailedReadinessParse(#[source] serde_json::Error),
+    #[error("Failed to parse event metadata")]
+    FailedMetadataParse(#[source] serde_json::Error),
 }
 
 #[derive(Error, Debug)]
 pub enum FetchError {
-    #[error("Failed to evaluate JS")]
-    FailedEval,
-    #[error("Evaluated JS returned no value")]
-    EvalNoValue,
+    #[error("Failed to evaluate JS on {url}")]
+    FailedEval {
+        url: Url,
+        #[source]
+        source: anyhow::Error,
+    },
+    #[error("Evaluated JS on {url} threw: {exception}")]
+    JsException { url: Url, exception: String },
+    #[error("Evaluated JS on {url} returned no value")]
+    EvalNoValue { url: Url },
+    #[error("Availability on {url} was still loading after {waited:?}: {state}")]
+    NotReady {
+        url: Url,
+        waited: std::time::Duration,
+        state: Box<str>,
+    },
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -20,28 +44,275 @@ pub enum ProcessResultError {
     AvailMatrixNoNext { section: Box<str> },
     #[error("Failed to parse timestamp from availability matrix: {timestamp}")]
     AvailMatrixFailedTimestampParse { timestamp: String },
+    #[error("Found {names} people names but {ids} people IDs")]
+    NamesIdsMismatch { names: usize, ids: usize },
+    #[error("Found {slots} slot timestamps but {rows} availability matrix rows")]
+    SlotsRowsMismatch { slots: usize, rows: usize },
+    #[error("Availability matrix row {row} has {found} entries, expected one for each of the {expected} people")]
+    AvailMatrixRowLength {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error(
+        "Availability matrix row {row} starts at {timestamp}, which is not after the previous row"
+    )]
+    AvailMatrixTimestampNotIncreasing { row: usize, timestamp: String },
+    #[error("Can't merge events for specific dates with events for days of the week")]
+    MixedEventModes,
+    #[error("Availability matrix row {row} starts at {timestamp}, too late for the slot to end")]
+    SlotOutOfRange { row: usize, timestamp: String },
 }
 
 #[derive(Error, Debug)]
 pub enum HeadlessChromeError {
     #[error("Failed to launch headless chrome")]
-    FailedToLaunch,
+    FailedToLaunch(#[source] anyhow::Error),
     #[error("Failed to create new tab")]
-    FailedToNewTab,
-    #[error("Failed to navigate to URL")]
-    FailedToNavigate,
-    #[error("Failed to wait until navigated")]
-    FailedToWaitUntilNavigated,
+    FailedToNewTab(#[source] anyhow::Error),
+    #[error("Failed to navigate to {url}")]
+    FailedToNavigate {
+        url: Url,
+        #[source]
+        source: anyhow::Error,
+    },
+    #[error("Failed to wait until navigated to {url}")]
+    FailedToWaitUntilNavigated {
+        url: Url,
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum HttpError {
+    #[error("Failed to request {url}")]
+    FailedRequest {
+        url: Url,
+        #[source]
+        source: Box<ureq::Error>,
+    },
+    #[error("Failed to read page body of {url}")]
+    FailedToReadBody {
+        url: Url,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum FileError {
+    #[error("Failed to read file {path}")]
+    FailedToRead {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("Failed to read snapshot file {path}")]
+    FailedToRead {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Failed to deserialize snapshot")]
+    FailedToDeserialize(#[source] serde_json::Error),
+    #[error("Failed to serialize snapshot")]
+    FailedToSerialize(#[source] serde_json::Error),
+    #[error("Unsupported snapshot version: {version}")]
+    UnsupportedVersion { version: u32 },
+}
+
+#[derive(Error, Debug)]
+pub enum CacheError {
+    #[error("No cached copy of {url}; run without --offline to fetch it")]
+    Missing { url: Url },
+    #[error("Failed to read cache file {path}")]
+    FailedToRead {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Failed to deserialize cache file {path}")]
+    FailedToDeserialize {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Failed to serialize cache entry")]
+    FailedToSerialize(#[source] serde_json::Error),
+    #[error("Failed to write cache file {path}")]
+    FailedToWrite {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum AliasError {
+    #[error("Failed to read alias file {path}")]
+    FailedToRead {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Failed to parse aliases")]
+    FailedToParse(#[source] toml::de::Error),
+    #[error("'{name}' is listed as an alias of both '{first}' and '{second}'")]
+    Conflict {
+        name: String,
+        first: String,
+        second: String,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("Failed to read CSV")]
+    Csv(#[source] csv::Error),
+    #[error("The first CSV column must be 'timestamp'")]
+    MissingTimestampColumn,
+    #[error("Person '{name}' is listed more than once, or has no name")]
+    DuplicatePerson { name: String },
+    #[error("Slot {row} has an invalid timestamp '{timestamp}'; expected RFC 3339, e.g. 2023-09-03T13:00:00Z, or unix seconds")]
+    InvalidTimestamp { row: usize, timestamp: String },
+    #[error("Slot {row} has an invalid availability '{value}' for {person}; expected e.g. 1 or 0")]
+    InvalidCell {
+        row: usize,
+        person: String,
+        value: String,
+    },
+    #[error("Failed to parse availability JSON")]
+    Json(#[source] serde_json::Error),
+    #[error("Slot {row} lists '{name}' as available, who is not one of the people")]
+    UnknownPerson { row: usize, name: String },
+    #[error("Slot {row} starts at {timestamp}, too late for the slot to end")]
+    TimestampOutOfRange { row: usize, timestamp: String },
+}
+
+#[derive(Error, Debug)]
+pub enum IcsError {
+    #[error("Failed to read calendar file {path}")]
+    FailedToRead {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Not an iCalendar file; expected it to start with BEGIN:VCALENDAR")]
+    NotACalendar,
+    #[error("Line {line} is not a NAME:VALUE property")]
+    InvalidLine { line: usize },
+    #[error("Line {line} has an invalid date or time '{value}'")]
+    InvalidDateTime { line: usize, value: String },
+    #[error("Line {line} uses the time zone '{tzid}', which is not an IANA time zone such as America/New_York, a Windows time zone or defined by the file")]
+    UnknownTimezone { line: usize, tzid: String },
+    #[error("Line {line} has an invalid duration '{value}'")]
+    InvalidDuration { line: usize, value: String },
+    #[error("Line {line} has an invalid recurrence rule '{rule}'")]
+    InvalidRule { line: usize, rule: String },
+    #[error("Line {line} has a recurrence rule with {part}, which is not supported")]
+    UnsupportedRule { line: usize, part: String },
+    #[error("The event starting on line {line} has no DTSTART")]
+    MissingStart { line: usize },
+}
+
+#[derive(Error, Debug)]
+pub enum CalDavError {
+    #[error("Failed to query the free/busy of {url}")]
+    FailedRequest {
+        url: Url,
+        #[source]
+        source: Box<ureq::Error>,
+    },
+    #[error("{url} refused the CalDAV credentials; set them in the [caldav] config section or the WHEN3MEET_CALDAV_USERNAME and WHEN3MEET_CALDAV_PASSWORD environment variables")]
+    Unauthorized { url: Url },
+    #[error("Failed to read the free/busy response of {url}")]
+    FailedToReadBody {
+        url: Url,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{url} did not respond with free/busy iCalendar data")]
+    InvalidResponse {
+        url: Url,
+        #[source]
+        source: Box<IcsError>,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum CrabFitError {
+    #[error("'{event}' is not a Crab.fit event; expected a link such as https://crab.fit/team-sync-123456 or its ID")]
+    InvalidEvent { event: String },
+    #[error("Crab.fit slot '{time}' is neither HHmm-DDMMYYYY nor HHmm-d")]
+    InvalidTime { time: String },
+    #[error("Failed to deserialize the Crab.fit response from {url}")]
+    FailedToDeserialize {
+        url: Url,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum InvalidUrlError {
+    #[error("Failed to parse URL")]
+    Unparseable(#[source] url::ParseError),
+    #[error("{url} is not a when2meet link; expected https://www.when2meet.com/?<id>-<code>")]
+    NotWhen2Meet { url: Url },
+    #[error("{url} does not point to an event; copy the full link, including the part after '?'")]
+    MissingEvent { url: Url },
+    #[error("{url} has a malformed event '{event}'; expected '<id>-<code>', e.g. ?21240105-aBcDe")]
+    MalformedEvent { url: Url, event: String },
 }
 
 #[derive(Error, Debug)]
 pub enum ParseWhen2MeetError {
-    #[error("Fetch error: {0}")]
+    #[error(transparent)]
+    InvalidUrl(InvalidUrlError),
+    #[error("{url} was not found; the event may have been deleted, check the link")]
+    EventNotFound { url: Url },
+    #[error(
+        "{location} is not a when2meet event page; the event may have been deleted, check the link"
+    )]
+    NotAnEvent { location: Box<str> },
+    #[error(transparent)]
     Fetch(FetchError),
-    #[error("Parse error: {0}")]
+    #[error(transparent)]
     Parse(ParseError),
-    #[error("Process result error: {0}")]
+    #[error(transparent)]
     ProcessResult(ProcessResultError),
-    #[error("Headless Chrome error: {0}")]
+    #[error(transparent)]
     HeadlessChrome(HeadlessChromeError),
+    #[error(transparent)]
+    Http(HttpError),
+    #[error(transparent)]
+    File(FileError),
+    #[error(transparent)]
+    Snapshot(SnapshotError),
+    #[error(transparent)]
+    Cache(CacheError),
+    #[error(transparent)]
+    Import(ImportError),
+    #[error(transparent)]
+    CrabFit(CrabFitError),
+    #[error("Gave up after {attempts} attempts")]
+    RetriesExhausted {
+        attempts: u32,
+        #[source]
+        source: Box<ParseWhen2MeetError>,
+    },
+    #[error("Timed out after {timeout:?} and {attempts} attempt(s)")]
+    TimedOut {
+        attempts: u32,
+        timeout: std::time::Duration,
+        #[source]
+        source: Box<ParseWhen2MeetError>,
+    },
+    #[error(transparent)]
+    Custom(Box<dyn std::error::Error + Send + Sync>),
 }
@@ -0,0 +1,611 @@
+use crate::fetch_availability::errors::{HttpError, ParseError, ParseWhen2MeetError};
+use crate::fetch_availability::model::{Event, EventMetadata};
+use crate::fetch_availability::parse::process_names_and_matrix;
+use crate::fetch_availability::snapshot::Snapshot;
+use std::collections::BTreeMap;
+use url::Url;
+
+pub fn parse_when2meet_http(url: &Url) -> Result<Event, ParseWhen2MeetError> {
+    parse_when2meet_html(&fetch_html(url)?)
+}
+
+pub fn snapshot_when2meet_http(url: &Url) -> Result<Snapshot, ParseWhen2MeetError> {
+    let mut snapshot = snapshot_when2meet_html(&fetch_html(url)?)?;
+
+    snapshot.url = Some(url.clone());
+
+    Ok(snapshot)
+}
+
+fn fetch_html(url: &Url) -> Result<String, ParseWhen2MeetError> {
+    let response = match ureq::get(url.as_str()).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404 | 410, _)) => {
+            return Err(ParseWhen2MeetError::EventNotFound { url: url.clone() })
+        }
+        Err(source) => {
+            return Err(ParseWhen2MeetError::Http(HttpError::FailedRequest {
+                url: url.clone(),
+                source: Box::new(source),
+            }))
+        }
+    };
+
+    let html = match response.into_string() {
+        Ok(html) => html,
+        Err(source) => {
+            return Err(ParseWhen2MeetError::Http(HttpError::FailedToReadBody {
+                url: url.clone(),
+                source,
+            }))
+        }
+    };
+
+    if !is_event_page(&html) {
+        return Err(ParseWhen2MeetError::NotAnEvent {
+            location: url.to_string().into_boxed_str(),
+        });
+    }
+
+    Ok(html)
+}
+
+/// Deleted events and other non-event pages load fine but never assign the event globals.
+pub(crate) fn is_event_page(html: &str) -> bool {
+    ["PeopleNames", "AvailableAtSlot", "TimeOfSlot"]
+        .iter()
+        .all(|array| is_assigned(html, array))
+}
+
+/// Whether the page assigns `array` itself, e.g. `var TimeOfSlot = new Array();`, or one of its
+/// entries, e.g. `TimeOfSlot[0]=1693746000;`.
+fn is_assigned(html: &str, array: &str) -> bool {
+    html.match_indices(array).any(|(start, _)| {
+        let rest = &html[start + array.len()..];
+        let rest = match rest.strip_prefix('[') {
+            Some(rest) => match rest.find(']') {
+                Some(close) => &rest[close + 1..],
+                None => return false,
+            },
+            None => rest,
+        };
+        assigned_value(rest).is_some()
+    })
+}
+
+pub fn parse_when2meet_html(html: &str) -> Result<Event, ParseWhen2MeetError> {
+    let (names, ids, avail_matrix) = match extract_all(html) {
+        Ok(extracted) => extracted,
+        Err(parse_error) => return Err(ParseWhen2MeetError::Parse(parse_error)),
+    };
+
+    let event = match process_names_and_matrix(names, ids, avail_matrix) {
+        Ok(event) => event,
+        Err(process_result_error) => {
+            return Err(ParseWhen2MeetError::ProcessResult(process_result_error))
+        }
+    };
+
+    Ok(event.with_metadata(extract_metadata(html)))
+}
+
+pub fn snapshot_when2meet_html(html: &str) -> Result<Snapshot, ParseWhen2MeetError> {
+    let (names, ids, avail_matrix) = match extract_all(html) {
+        Ok(extracted) => extracted,
+        Err(parse_error) => return Err(ParseWhen2MeetError::Parse(parse_error)),
+    };
+
+    let mut snapshot = match Snapshot::from_raw(names, ids, avail_matrix) {
+        Ok(snapshot) => snapshot,
+        Err(process_result_error) => {
+            return Err(ParseWhen2MeetError::ProcessResult(process_result_error))
+        }
+    };
+
+    snapshot.metadata = extract_metadata(html);
+
+    Ok(snapshot)
+}
+
+/// The names, IDs and avail matrix rows read off an event page.
+type Extracted = (Vec<Box<str>>, Vec<i64>, Vec<Box<str>>);
+
+fn extract_all(html: &str) -> Result<Extracted, ParseError> {
+    let names = extract_people_names(html)?;
+    let avail_matrix = extract_avail_matrix(html)?;
+    let ids = extract_people_ids(html)?.into_values().collect();
+
+    Ok((names, ids, avail_matrix))
+}
+
+/// Reads the same title and timezone as `fetch_metadata` does in the browser, falling back to the
+/// page `<title>` for the event name.
+fn extract_metadata(html: &str) -> EventMetadata {
+    let title = element_text(html, "NewEventNameDiv").or_else(|| {
+        let title = element_text(html, "title")?;
+        Some(
+            title
+                .strip_suffix(" - When2meet")
+                .unwrap_or(&title)
+                .to_string()
+                .into_boxed_str(),
+        )
+    });
+
+    let timezone = html.find(r#"id="ParticipantTimeZone""#).and_then(|start| {
+        let select = &html[start..];
+        let select = &select[..select.find("</select>")?];
+        select
+            .split("<option")
+            .find(|option| option.contains("selected"))
+            .and_then(|option| attribute_value(option, "value"))
+    });
+
+    EventMetadata {
+        title: title.filter(|title| !title.is_empty()),
+        timezone,
+    }
+}
+
+/// The decoded text of the first element whose ID, or tag name when the ID isn't found, matches.
+fn element_text(html: &str, id_or_tag: &str) -> Option<Box<str>> {
+    let start = html
+        .find(&format!(r#"id="{}""#, id_or_tag))
+        .or_else(|| html.find(&format!("<{}", id_or_tag)))?;
+    let rest = &html[start..];
+    let rest = &rest[rest.find('>')? + 1..];
+    let text = &rest[..rest.find('<')?];
+
+    Some(decode_entities(text.trim()).into_boxed_str())
+}
+
+fn attribute_value(tag: &str, attribute: &str) -> Option<Box<str>> {
+    let pattern = format!(r#"{}=""#, attribute);
+    let rest = &tag[tag.find(&pattern)? + pattern.len()..];
+
+    Some(decode_entities(&rest[..rest.find('"')?]).into_boxed_str())
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#039;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Empty when nobody has responded to the event yet.
+fn extract_people_names(html: &str) -> Result<Vec<Box<str>>, ParseError> {
+    let names = parse_entries(html, "PeopleNames", parse_string_literal)?;
+
+    Ok(names.into_values().collect())
+}
+
+fn extract_people_ids(html: &str) -> Result<BTreeMap<usize, i64>, ParseError> {
+    parse_entries(html, "PeopleIDs", parse_integer)
+}
+
+/// Parses the value assigned to every entry of `array`, failing on the first one that can't be
+/// parsed rather than skipping it, so names and IDs never fall out of step.
+fn parse_entries<T>(
+    html: &str,
+    array: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<BTreeMap<usize, T>, ParseError> {
+    indexed_entries(html, array)
+        .into_iter()
+        .map(
+            |(index, rest)| match assigned_value(rest).and_then(&parse) {
+                Some(value) => Ok((index, value)),
+                None => Err(ParseError::FailedEntryParse {
+                    entry: entry_statement(array, index, rest),
+                }),
+            },
+        )
+        .collect()
+}
+
+/// The statement assigning `<array>[<index>]`, up to the end of its line or the next `;`.
+fn entry_statement(array: &str, index: usize, rest: &str) -> Box<str> {
+    let end = rest.find([';', '\n']).unwrap_or(rest.len());
+
+    format!("{}[{}]{}", array, index, rest[..end].trim_end()).into_boxed_str()
+}
+
+/// Builds the same `<timestamp>,<0|1>,...` rows that `fetch_avail_matrix` evaluates in the browser.
+fn extract_avail_matrix(html: &str) -> Result<Vec<Box<str>>, ParseError> {
+    let people_ids = extract_people_ids(html)?;
+
+    let time_of_slot = parse_entries(html, "TimeOfSlot", parse_integer)?;
+
+    let mut available_at_slot: BTreeMap<usize, Vec<i64>> = BTreeMap::new();
+    for (index, rest) in indexed_entries(html, "AvailableAtSlot") {
+        if let Some(id) = rest.strip_prefix(".push(").and_then(parse_integer) {
+            available_at_slot.entry(index).or_default().push(id);
+        }
+    }
+
+    if time_of_slot.is_empty() {
+        return Err(ParseError::EmptyRaw);
+    }
+
+    Ok(time_of_slot
+        .iter()
+        .map(|(index, timestamp)| {
+            let available_ids = available_at_slot.get(index);
+            let row = people_ids
+                .values()
+                .map(|id| match available_ids {
+                    Some(ids) if ids.contains(id) => ",1",
+                    _ => ",0",
+                })
+                .collect::<String>();
+            format!("{}{}", timestamp, row).into_boxed_str()
+        })
+        .collect())
+}
+
+/// Finds every `<array>[<index>]` in the page with a literal numeric index and returns the index
+/// along with the text directly following the closing bracket.
+fn indexed_entries<'a>(html: &'a str, array: &str) -> Vec<(usize, &'a str)> {
+    let pattern = format!("{}[", array);
+
+    html.match_indices(&pattern)
+        .filter_map(|(start, _)| {
+            let rest = &html[start + pattern.len()..];
+            let close = rest.find(']')?;
+            let index = rest[..close].parse().ok()?;
+            Some((index, &rest[close + 1..]))
+        })
+        .collect()
+}
+
+fn assigned_value(rest: &str) -> Option<&str> {
+    Some(rest.trim_start().strip_prefix('=')?.trim_start())
+}
+
+fn parse_integer(value: &str) -> Option<i64> {
+    let end = value
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || (i == 0 && c == '-')))
+        .map_or(value.len(), |(i, _)| i);
+
+    value[..end].parse().ok()
+}
+
+fn parse_string_literal(value: &str) -> Option<Box<str>> {
+    let mut chars = value.chars();
+    let quote = chars.next().filter(|c| *c == '\'' || *c == '"')?;
+    let mut literal = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                'n' => literal.push('\n'),
+                't' => literal.push('\t'),
+                'r' => literal.push('\r'),
+                'u' => {
+                    let code = parse_code_unit(&mut chars)?;
+                    // Characters outside the BMP, like most emoji, are escaped as a surrogate pair.
+                    let code = if (0xD800..0xDC00).contains(&code) {
+                        if chars.next()? != '\\' || chars.next()? != 'u' {
+                            return None;
+                        }
+                        let low = parse_code_unit(&mut chars)?;
+                        0x10000 + ((code - 0xD800) << 10) + low.checked_sub(0xDC00)?
+                    } else {
+                        code
+                    };
+                    literal.push(char::from_u32(code)?);
+                }
+                escaped => literal.push(escaped),
+            },
+            c if c == quote => return Some(literal.into_boxed_str()),
+            c => literal.push(c),
+        }
+    }
+
+    None
+}
+
+fn parse_code_unit(chars: &mut std::str::Chars) -> Option<u32> {
+    let code: String = chars.by_ref().take(4).collect();
+    u32::from_str_radix(&code, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::errors::{HttpError, ParseError, ParseWhen2MeetError};
+    use crate::fetch_availability::html::{
+        extract_avail_matrix, extract_metadata, extract_people_names, is_event_page,
+        parse_when2meet_html, parse_when2meet_http, snapshot_when2meet_html,
+    };
+    use crate::fetch_availability::model::{Event, EventMetadata};
+    use crate::fetch_availability::parse::process_names_and_matrix;
+    use claims::{assert_err, assert_ok};
+    use std::thread;
+    use url::Url;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/when2meet_event.html");
+
+    fn expected_event() -> Event {
+        let names = vec![
+            "Muneer".to_string().into_boxed_str(),
+            "Brian".to_string().into_boxed_str(),
+            "Garrett".to_string().into_boxed_str(),
+        ];
+
+        let avail_matrix = vec![
+            "1693746000,0,0,0".to_string().into_boxed_str(),
+            "1693746900,1,0,0".to_string().into_boxed_str(),
+            "1693747800,1,1,0".to_string().into_boxed_str(),
+            "1693748700,1,1,1".to_string().into_boxed_str(),
+        ];
+
+        let ids = vec![94102857, 94102860, 94102911];
+
+        process_names_and_matrix(names, ids, avail_matrix)
+            .unwrap()
+            .with_metadata(expected_metadata())
+    }
+
+    fn expected_metadata() -> EventMetadata {
+        EventMetadata {
+            title: Some("Team Sync".to_string().into_boxed_str()),
+            timezone: Some("America/New_York".to_string().into_boxed_str()),
+        }
+    }
+
+    #[test]
+    fn test_extract_people_names() {
+        let names = extract_people_names(FIXTURE).unwrap();
+
+        assert_eq!(
+            names,
+            vec![
+                "Muneer".to_string().into_boxed_str(),
+                "Brian".to_string().into_boxed_str(),
+                "Garrett".to_string().into_boxed_str(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_people_names_escaped_quote() {
+        let html = r#"PeopleNames[0] = 'O\'Brien';PeopleIDs[0] = 1;"#;
+
+        let names = extract_people_names(html).unwrap();
+
+        assert_eq!(names, vec!["O'Brien".to_string().into_boxed_str()]);
+    }
+
+    #[test]
+    fn test_extract_people_names_punctuation_and_unicode() {
+        let html = r#"PeopleNames[0] = 'Smith, Jane';PeopleIDs[0] = 1;
+PeopleNames[1] = "O'Brien";PeopleIDs[1] = 2;
+PeopleNames[2] = 'Zo\u00eb \ud83e\udd80';PeopleIDs[2] = 3;
+PeopleNames[3] = 'Zoë 🦀';PeopleIDs[3] = 4;"#;
+
+        let names = extract_people_names(html).unwrap();
+
+        assert_eq!(
+            names,
+            vec![
+                "Smith, Jane".to_string().into_boxed_str(),
+                "O'Brien".to_string().into_boxed_str(),
+                "Zoë 🦀".to_string().into_boxed_str(),
+                "Zoë 🦀".to_string().into_boxed_str(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_people_names_no_respondents() {
+        let names = extract_people_names("<html></html>").unwrap();
+
+        assert!(names.is_empty());
+    }
+
+    #[test]
+    fn test_extract_people_names_unparsable() {
+        let html = "PeopleNames[0] = 'Muneer';PeopleIDs[0] = 1;\nPeopleNames[1] = 'Brian\\u00';PeopleIDs[1] = 2;";
+
+        let names = extract_people_names(html);
+
+        assert_err!(&names);
+
+        assert!(matches!(
+            names.unwrap_err(),
+            ParseError::FailedEntryParse { entry } if *entry == *r"PeopleNames[1] = 'Brian\u00'"
+        ));
+    }
+
+    #[test]
+    fn test_extract_avail_matrix_unparsable_id() {
+        let html = "PeopleNames[0] = 'Muneer';PeopleIDs[0] = someId;\nTimeOfSlot[0]=1693746000;";
+
+        let avail_matrix = extract_avail_matrix(html);
+
+        assert_err!(&avail_matrix);
+
+        assert!(matches!(
+            avail_matrix.unwrap_err(),
+            ParseError::FailedEntryParse { entry } if *entry == *"PeopleIDs[0] = someId"
+        ));
+    }
+
+    #[test]
+    fn test_is_event_page() {
+        assert!(is_event_page(FIXTURE));
+        assert!(is_event_page(
+            "var PeopleNames = new Array();var AvailableAtSlot = new Array();TimeOfSlot[0]=1693746000;"
+        ));
+        assert!(!is_event_page(
+            "<p>TimeOfSlot, PeopleNames and AvailableAtSlot</p>"
+        ));
+        assert!(!is_event_page("<script>TimeOfSlot[0]=1693746000;</script>"));
+    }
+
+    #[test]
+    fn test_extract_avail_matrix() {
+        let avail_matrix = extract_avail_matrix(FIXTURE);
+
+        assert_ok!(&avail_matrix);
+
+        let avail_matrix = avail_matrix.unwrap();
+
+        assert_eq!(
+            avail_matrix,
+            vec![
+                "1693746000,0,0,0".to_string().into_boxed_str(),
+                "1693746900,1,0,0".to_string().into_boxed_str(),
+                "1693747800,1,1,0".to_string().into_boxed_str(),
+                "1693748700,1,1,1".to_string().into_boxed_str(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_metadata() {
+        assert_eq!(extract_metadata(FIXTURE), expected_metadata());
+    }
+
+    #[test]
+    fn test_extract_metadata_title_fallback() {
+        let html = "<html><head><title>Lunch &amp; Learn - When2meet</title></head></html>";
+
+        assert_eq!(
+            extract_metadata(html),
+            EventMetadata {
+                title: Some("Lunch & Learn".to_string().into_boxed_str()),
+                timezone: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_when2meet_html() {
+        let event = parse_when2meet_html(FIXTURE);
+
+        assert_ok!(&event);
+
+        assert_eq!(event.unwrap(), expected_event());
+    }
+
+    #[test]
+    fn test_parse_when2meet_html_not_an_event() {
+        let event = parse_when2meet_html("<html><body>When2meet</body></html>");
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::Parse(ParseError::EmptyRaw)
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_when2meet_html() {
+        let snapshot = snapshot_when2meet_html(FIXTURE);
+
+        assert_ok!(&snapshot);
+
+        let snapshot = snapshot.unwrap();
+
+        assert_eq!(snapshot.people_ids, vec![94102857, 94102860, 94102911]);
+        assert_eq!(snapshot.metadata, expected_metadata());
+        assert_eq!(snapshot.time_of_slot.len(), 4);
+        assert_eq!(snapshot.event().unwrap(), expected_event());
+    }
+
+    #[test]
+    fn test_parse_when2meet_http() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/?21240105-aBcDe", server.server_addr())).unwrap();
+
+        let handle = thread::spawn(move || {
+            let request = server.recv().unwrap();
+            request
+                .respond(tiny_http::Response::from_string(FIXTURE))
+                .unwrap();
+        });
+
+        let event = parse_when2meet_http(&url);
+
+        handle.join().unwrap();
+
+        assert_ok!(&event);
+
+        assert_eq!(event.unwrap(), expected_event());
+    }
+
+    #[test]
+    fn test_parse_when2meet_http_connection_refused() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/?21240105-aBcDe", server.server_addr())).unwrap();
+        drop(server);
+
+        let event = parse_when2meet_http(&url);
+
+        assert_err!(&event);
+
+        let error = event.unwrap_err();
+
+        assert!(std::error::Error::source(&error).is_some());
+        assert!(matches!(
+            error,
+            ParseWhen2MeetError::Http(HttpError::FailedRequest { url: failed_url, .. }) if failed_url == url
+        ));
+    }
+
+    #[test]
+    fn test_parse_when2meet_http_event_not_found() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/?21240105-aBcDe", server.server_addr())).unwrap();
+
+        let handle = thread::spawn(move || {
+            let request = server.recv().unwrap();
+            request
+                .respond(tiny_http::Response::from_string("Not Found").with_status_code(404))
+                .unwrap();
+        });
+
+        let event = parse_when2meet_http(&url);
+
+        handle.join().unwrap();
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::EventNotFound { url: missing_url } if missing_url == url
+        ));
+    }
+
+    #[test]
+    fn test_parse_when2meet_http_not_an_event() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/?21240105-aBcDe", server.server_addr())).unwrap();
+
+        let handle = thread::spawn(move || {
+            let request = server.recv().unwrap();
+            request
+                .respond(tiny_http::Response::from_string(
+                    "<html><body>When2meet</body></html>",
+                ))
+                .unwrap();
+        });
+
+        let event = parse_when2meet_http(&url);
+
+        handle.join().unwrap();
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::NotAnEvent { location } if *location == *url.as_str()
+        ));
+    }
+}
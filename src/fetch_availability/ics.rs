@@ -0,0 +1,1263 @@
+use crate::fetch_availability::errors::IcsError;
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A busy stretch of time, from its start up to but not including its end.
+pub type Period = (DateTime<Utc>, DateTime<Utc>);
+
+/// The zones of the TZIDs defined by a file's own VTIMEZONE components.
+type Timezones = HashMap<String, Tz>;
+
+/// The busy blocks of an iCalendar file: its events, unless marked free or cancelled, and the busy
+/// periods of its free/busy components.
+#[derive(Debug, Default, PartialEq)]
+pub struct Calendar {
+    blocks: Vec<Block>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Block {
+    uid: Option<Box<str>>,
+    start: Moment,
+    duration: Duration,
+    rule: Option<Rule>,
+    /// Occurrences removed with EXDATE or replaced by an event with a RECURRENCE-ID.
+    exceptions: Vec<Moment>,
+}
+
+/// A wall-clock time and the zone it is in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Moment {
+    local: NaiveDateTime,
+    zone: Zone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Utc,
+    Named(Tz),
+    /// No zone given, e.g. an all-day event, so it is in whatever zone the reader is in.
+    Floating,
+}
+
+#[derive(Debug, PartialEq)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<Moment>,
+    by_day: Vec<Weekday>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A content line, e.g. `DTSTART;TZID=America/New_York:20230903T091500`.
+struct Property<'a> {
+    line: usize,
+    name: String,
+    params: Vec<(String, &'a str)>,
+    value: &'a str,
+}
+
+impl Property<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.trim_matches('"'))
+    }
+}
+
+/// The properties of a VEVENT read so far.
+#[derive(Default)]
+struct EventBuilder {
+    line: usize,
+    uid: Option<Box<str>>,
+    start: Option<(Moment, bool)>,
+    end: Option<Moment>,
+    duration: Option<Duration>,
+    rule: Option<Rule>,
+    exceptions: Vec<Moment>,
+    recurrence_id: Option<Moment>,
+    transparent: bool,
+    cancelled: bool,
+    /// Why the event is skipped, e.g. a recurrence rule that isn't supported.
+    unsupported: Option<IcsError>,
+}
+
+impl Calendar {
+    pub fn read(path: &Path) -> Result<Self, IcsError> {
+        let ics = match std::fs::read_to_string(path) {
+            Ok(ics) => ics,
+            Err(source) => {
+                return Err(IcsError::FailedToRead {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+
+        Self::parse(&ics)
+    }
+
+    /// Events that can't be read faithfully, e.g. because of an unsupported recurrence rule or an
+    /// unknown time zone, are skipped with a warning rather than failing the whole file.
+    pub fn parse(ics: &str) -> Result<Self, IcsError> {
+        let mut components: Vec<String> = Vec::new();
+        let mut event = EventBuilder::default();
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut overrides: HashMap<Box<str>, Vec<Moment>> = HashMap::new();
+
+        let lines = unfold(ics.trim_start_matches('\u{feff}'));
+
+        match lines.first() {
+            Some((_, first)) if first.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR") => {}
+            _ => return Err(IcsError::NotACalendar),
+        }
+
+        let zones = defined_timezones(&lines);
+
+        for (line, content) in lines {
+            let property = parse_property(line, &content)?;
+
+            match property.name.as_str() {
+                "BEGIN" => {
+                    let component = property.value.to_uppercase();
+
+                    if component == "VEVENT" {
+                        event = EventBuilder {
+                            line,
+                            ..EventBuilder::default()
+                        };
+                    }
+
+                    components.push(component);
+                    continue;
+                }
+                "END" => {
+                    if components.pop().as_deref() == Some("VEVENT") {
+                        let event = std::mem::take(&mut event);
+
+                        if let Some(error) = &event.unsupported {
+                            log::warn!(
+                                "Skipping the event starting on line {}: {}",
+                                event.line,
+                                error
+                            );
+                            continue;
+                        }
+
+                        if let (Some(uid), Some(recurrence_id)) = (&event.uid, event.recurrence_id)
+                        {
+                            overrides
+                                .entry(uid.clone())
+                                .or_default()
+                                .push(recurrence_id);
+                        }
+
+                        if let Some(block) = event.build()? {
+                            blocks.push(block);
+                        }
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            match components.last().map(String::as_str) {
+                Some("VEVENT") if event.unsupported.is_none() => {
+                    match event.set(&property, &zones) {
+                        Ok(()) => {}
+                        Err(error) if is_unsupported(&error) => event.unsupported = Some(error),
+                        Err(error) => return Err(error),
+                    }
+                }
+                Some("VFREEBUSY") if property.name == "FREEBUSY" => {
+                    match parse_free_busy(&property, &zones) {
+                        Ok(busy) => blocks.extend(busy),
+                        Err(error) if is_unsupported(&error) => {
+                            log::warn!("Skipping the free/busy on line {}: {}", line, error)
+                        }
+                        Err(error) => return Err(error),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for block in &mut blocks {
+            if let (Some(uid), Some(_)) = (&block.uid, &block.rule) {
+                block
+                    .exceptions
+                    .extend(overrides.get(uid).into_iter().flatten());
+            }
+        }
+
+        Ok(Self { blocks })
+    }
+
+    /// The busy periods that overlap `from` to `to`, with recurring events expanded. Times
+    /// without a time zone, including all-day events, are taken to be in `floating`.
+    pub fn busy(&self, from: DateTime<Utc>, to: DateTime<Utc>, floating: Tz) -> Vec<Period> {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.busy(from, to, floating))
+            .collect()
+    }
+}
+
+impl Block {
+    fn busy(&self, from: DateTime<Utc>, to: DateTime<Utc>, floating: Tz) -> Vec<Period> {
+        let period = |local: NaiveDateTime| -> Period {
+            (
+                Moment {
+                    local,
+                    ..self.start
+                }
+                .to_utc(floating),
+                Moment {
+                    local: local + self.duration,
+                    ..self.start
+                }
+                .to_utc(floating),
+            )
+        };
+        let overlaps = |(start, end): &Period| *start < to && from < *end;
+
+        let rule = match &self.rule {
+            Some(rule) => rule,
+            None => {
+                return [period(self.start.local)]
+                    .into_iter()
+                    .filter(overlaps)
+                    .collect()
+            }
+        };
+
+        let exceptions: Vec<DateTime<Utc>> = self
+            .exceptions
+            .iter()
+            .map(|exception| exception.to_utc(floating))
+            .collect();
+        let until = rule.until.map(|until| until.to_utc(floating));
+
+        // DTSTART is always the first occurrence, whether or not it matches the rule.
+        let mut starts = vec![self.start.local];
+        let mut count = 0;
+        let mut busy = Vec::new();
+
+        'periods: for index in 0.. {
+            let (period_start, candidates) = match rule.period(self.start.local, index) {
+                Some(period) => period,
+                None => break,
+            };
+
+            starts.extend(
+                candidates
+                    .into_iter()
+                    .filter(|candidate| *candidate > self.start.local),
+            );
+
+            for local in starts.drain(..) {
+                let (start, end) = period(local);
+
+                if start >= to
+                    || until.is_some_and(|until| start > until)
+                    || rule.count.is_some_and(|max| count >= max)
+                {
+                    break 'periods;
+                }
+
+                count += 1;
+
+                if overlaps(&(start, end)) && !exceptions.contains(&start) {
+                    busy.push((start, end));
+                }
+            }
+
+            if (Moment {
+                local: period_start,
+                ..self.start
+            })
+            .to_utc(floating)
+                >= to
+            {
+                break;
+            }
+        }
+
+        busy
+    }
+}
+
+impl EventBuilder {
+    fn set(&mut self, property: &Property, zones: &Timezones) -> Result<(), IcsError> {
+        match property.name.as_str() {
+            "UID" => self.uid = Some(property.value.to_string().into_boxed_str()),
+            "DTSTART" => self.start = Some(parse_moment(property, property.value, zones)?),
+            "DTEND" => self.end = Some(parse_moment(property, property.value, zones)?.0),
+            "DURATION" => self.duration = Some(parse_duration(property.line, property.value)?),
+            "RRULE" => self.rule = Some(parse_rule(property, zones)?),
+            "EXDATE" => {
+                for value in property.value.split(',') {
+                    self.exceptions
+                        .push(parse_moment(property, value, zones)?.0);
+                }
+            }
+            "RECURRENCE-ID" => {
+                self.recurrence_id = Some(parse_moment(property, property.value, zones)?.0)
+            }
+            "TRANSP" => self.transparent = property.value.eq_ignore_ascii_case("TRANSPARENT"),
+            "STATUS" => self.cancelled = property.value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// The busy block, or `None` if the event doesn't make anyone busy.
+    fn build(self) -> Result<Option<Block>, IcsError> {
+        let (start, all_day) = match self.start {
+            Some(start) => start,
+            None => return Err(IcsError::MissingStart { line: self.line }),
+        };
+
+        if self.transparent || self.cancelled {
+            return Ok(None);
+        }
+
+        let duration = match (self.end, self.duration) {
+            (Some(end), _) if end.zone == start.zone => end.local - start.local,
+            (Some(end), _) => end.to_utc(Tz::UTC) - start.to_utc(Tz::UTC),
+            (None, Some(duration)) => duration,
+            (None, None) if all_day => Duration::days(1),
+            (None, None) => Duration::zero(),
+        };
+
+        if duration <= Duration::zero() {
+            return Ok(None);
+        }
+
+        if start.local.checked_add_signed(duration).is_none() {
+            return Err(IcsError::InvalidDuration {
+                line: self.line,
+                value: duration.to_string(),
+            });
+        }
+
+        let rule = self.rule.map(|rule| Rule {
+            // A floating UNTIL is in the zone of the event it ends.
+            until: rule.until.map(|until| match until.zone {
+                Zone::Floating => Moment {
+                    zone: start.zone,
+                    ..until
+                },
+                _ => until,
+            }),
+            ..rule
+        });
+
+        Ok(Some(Block {
+            uid: self.uid,
+            start,
+            duration,
+            rule,
+            exceptions: self.exceptions,
+        }))
+    }
+}
+
+impl Moment {
+    fn to_utc(self, floating: Tz) -> DateTime<Utc> {
+        let tz = match self.zone {
+            Zone::Utc => return Utc.from_utc_datetime(&self.local),
+            Zone::Named(tz) => tz,
+            Zone::Floating => floating,
+        };
+
+        match tz.from_local_datetime(&self.local).earliest() {
+            Some(time) => time.with_timezone(&Utc),
+            // Skipped by the clocks going forward, so it happens an hour later.
+            None => tz
+                .from_local_datetime(&(self.local + Duration::hours(1)))
+                .earliest()
+                .map_or_else(
+                    || Utc.from_utc_datetime(&self.local),
+                    |time| time.with_timezone(&Utc),
+                ),
+        }
+    }
+}
+
+impl Rule {
+    /// The start of the `index`th interval of the rule and the occurrences in it, in the event's
+    /// own wall-clock time. `None` once the dates run out of range.
+    fn period(
+        &self,
+        dtstart: NaiveDateTime,
+        index: u32,
+    ) -> Option<(NaiveDateTime, Vec<NaiveDateTime>)> {
+        let step = i64::from(self.interval) * i64::from(index);
+        let time = dtstart.time();
+        let date = dtstart.date();
+
+        let (period_start, dates) = match self.frequency {
+            Frequency::Daily => {
+                let day = date.checked_add_signed(Duration::days(step))?;
+                let dates = if self.by_day.is_empty() || self.by_day.contains(&day.weekday()) {
+                    vec![day]
+                } else {
+                    Vec::new()
+                };
+                (day, dates)
+            }
+            Frequency::Weekly => {
+                let monday = date
+                    .checked_sub_signed(Duration::days(
+                        date.weekday().num_days_from_monday().into(),
+                    ))?
+                    .checked_add_signed(Duration::weeks(step))?;
+                let mut weekdays = if self.by_day.is_empty() {
+                    vec![date.weekday()]
+                } else {
+                    self.by_day.clone()
+                };
+                weekdays.sort_by_key(Weekday::num_days_from_monday);
+                let dates = weekdays
+                    .into_iter()
+                    .filter_map(|weekday| {
+                        monday.checked_add_signed(Duration::days(
+                            weekday.num_days_from_monday().into(),
+                        ))
+                    })
+                    .collect();
+                (monday, dates)
+            }
+            Frequency::Monthly => {
+                let months = i64::from(date.year()) * 12 + i64::from(date.month0()) + step;
+                let year = i32::try_from(months.div_euclid(12)).ok()?;
+                let month = u32::try_from(months.rem_euclid(12)).ok()? + 1;
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                // Months without the day, e.g. the 31st, are skipped.
+                let dates = NaiveDate::from_ymd_opt(year, month, date.day())
+                    .into_iter()
+                    .collect();
+                (first, dates)
+            }
+            Frequency::Yearly => {
+                let year = date.year().checked_add(i32::try_from(step).ok()?)?;
+                let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                let dates = NaiveDate::from_ymd_opt(year, date.month(), date.day())
+                    .into_iter()
+                    .collect();
+                (first, dates)
+            }
+        };
+
+        Some((
+            period_start.and_time(NaiveTime::MIN),
+            dates.into_iter().map(|date| date.and_time(time)).collect(),
+        ))
+    }
+}
+
+/// Joins lines continued with a leading space or tab, keeping the number of each first line.
+fn unfold(ics: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+
+    for (index, line) in ics.lines().enumerate() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some((_, previous))) => previous.push_str(continuation),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push((index + 1, line.to_string())),
+        }
+    }
+
+    lines
+}
+
+fn parse_property(line: usize, content: &str) -> Result<Property<'_>, IcsError> {
+    let mut in_quotes = false;
+    let mut separators = Vec::new();
+    let mut colon = None;
+
+    for (index, c) in content.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => separators.push(index),
+            ':' if !in_quotes => {
+                colon = Some(index);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let colon = match colon {
+        Some(colon) => colon,
+        None => return Err(IcsError::InvalidLine { line }),
+    };
+
+    let name_end = separators.first().copied().unwrap_or(colon);
+    let mut params = Vec::with_capacity(separators.len());
+
+    for (index, start) in separators.iter().enumerate() {
+        let end = separators.get(index + 1).copied().unwrap_or(colon);
+
+        match content[start + 1..end].split_once('=') {
+            Some((param, value)) => params.push((param.to_uppercase(), value)),
+            None => return Err(IcsError::InvalidLine { line }),
+        }
+    }
+
+    Ok(Property {
+        line,
+        name: content[..name_end].to_uppercase(),
+        params,
+        value: content[colon + 1..].trim(),
+    })
+}
+
+/// A DATE or DATE-TIME value in the zone given by the property's TZID, and whether it is a DATE.
+fn parse_moment(
+    property: &Property,
+    value: &str,
+    zones: &Timezones,
+) -> Result<(Moment, bool), IcsError> {
+    let invalid = || IcsError::InvalidDateTime {
+        line: property.line,
+        value: value.to_string(),
+    };
+    let value = value.trim();
+
+    let zone = match property.param("TZID") {
+        Some(tzid) => match resolve_timezone(tzid, zones) {
+            Some(tz) => Zone::Named(tz),
+            None => {
+                return Err(IcsError::UnknownTimezone {
+                    line: property.line,
+                    tzid: tzid.to_string(),
+                })
+            }
+        },
+        None => Zone::Floating,
+    };
+
+    if value.len() == 8 {
+        return match NaiveDate::parse_from_str(value, "%Y%m%d") {
+            Ok(date) => Ok((
+                Moment {
+                    local: date.and_time(NaiveTime::MIN),
+                    zone,
+                },
+                true,
+            )),
+            Err(_) => Err(invalid()),
+        };
+    }
+
+    let (value, zone) = match value.strip_suffix('Z') {
+        Some(value) => (value, Zone::Utc),
+        None => (value, zone),
+    };
+
+    match NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        Ok(local) => Ok((Moment { local, zone }, false)),
+        Err(_) => Err(invalid()),
+    }
+}
+
+/// The longest duration chrono can hold, in whole seconds.
+const MAX_DURATION_SECONDS: i64 = i64::MAX / 1000;
+
+/// An RFC 5545 duration, e.g. `PT1H30M`, `P1D` or `-P1W`.
+fn parse_duration(line: usize, value: &str) -> Result<Duration, IcsError> {
+    let invalid = || IcsError::InvalidDuration {
+        line,
+        value: value.to_string(),
+    };
+
+    let (sign, rest) = match value.trim().strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.trim().trim_start_matches('+')),
+    };
+
+    let rest = match rest.strip_prefix('P') {
+        Some(rest) => rest,
+        None => return Err(invalid()),
+    };
+
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+
+    for c in rest.chars() {
+        let unit_seconds: i64 = match (c, in_time) {
+            ('0'..='9', _) => {
+                number.push(c);
+                continue;
+            }
+            ('T', false) if number.is_empty() => {
+                in_time = true;
+                continue;
+            }
+            ('W', false) => 7 * 24 * 60 * 60,
+            ('D', false) => 24 * 60 * 60,
+            ('H', true) => 60 * 60,
+            ('M', true) => 60,
+            ('S', true) => 1,
+            _ => return Err(invalid()),
+        };
+
+        // Out of range amounts would make chrono panic, and can come from any calendar.
+        let seconds = number
+            .parse::<i64>()
+            .ok()
+            .and_then(|amount| amount.checked_mul(unit_seconds))
+            .filter(|seconds| *seconds <= MAX_DURATION_SECONDS);
+
+        duration =
+            match seconds.and_then(|seconds| duration.checked_add(&Duration::seconds(seconds))) {
+                Some(duration) => duration,
+                None => return Err(invalid()),
+            };
+
+        number.clear();
+    }
+
+    if number.is_empty() {
+        Ok(duration * sign)
+    } else {
+        Err(invalid())
+    }
+}
+
+fn parse_rule(property: &Property, zones: &Timezones) -> Result<Rule, IcsError> {
+    let invalid = || IcsError::InvalidRule {
+        line: property.line,
+        rule: property.value.to_string(),
+    };
+    let unsupported = |part: &str| IcsError::UnsupportedRule {
+        line: property.line,
+        part: part.to_string(),
+    };
+
+    let mut rule = Rule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+    };
+    let mut frequency = None;
+
+    for part in property.value.split(';').filter(|part| !part.is_empty()) {
+        let (name, value) = match part.split_once('=') {
+            Some((name, value)) => (name.to_uppercase(), value),
+            None => return Err(invalid()),
+        };
+
+        match name.as_str() {
+            "FREQ" => {
+                frequency = Some(match value.to_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    "SECONDLY" | "MINUTELY" | "HOURLY" => return Err(unsupported(part)),
+                    _ => return Err(invalid()),
+                })
+            }
+            "INTERVAL" => match value.parse() {
+                Ok(interval) if interval > 0 => rule.interval = interval,
+                _ => return Err(invalid()),
+            },
+            "COUNT" => match value.parse() {
+                Ok(count) => rule.count = Some(count),
+                Err(_) => return Err(invalid()),
+            },
+            "UNTIL" => {
+                let until = Property {
+                    params: Vec::new(),
+                    name: name.clone(),
+                    ..*property
+                };
+                rule.until = Some(parse_moment(&until, value, zones)?.0);
+            }
+            "BYDAY" => {
+                for day in value.split(',') {
+                    rule.by_day.push(match day.to_uppercase().as_str() {
+                        "MO" => Weekday::Mon,
+                        "TU" => Weekday::Tue,
+                        "WE" => Weekday::Wed,
+                        "TH" => Weekday::Thu,
+                        "FR" => Weekday::Fri,
+                        "SA" => Weekday::Sat,
+                        "SU" => Weekday::Sun,
+                        // e.g. 1MO, the first Monday of the month.
+                        _ => return Err(unsupported(part)),
+                    });
+                }
+            }
+            // Only changes the result for rules with more parts than are supported here.
+            "WKST" => {}
+            _ => return Err(unsupported(part)),
+        }
+    }
+
+    rule.frequency = match frequency {
+        Some(frequency) => frequency,
+        None => return Err(invalid()),
+    };
+
+    if !rule.by_day.is_empty() && matches!(rule.frequency, Frequency::Monthly | Frequency::Yearly) {
+        return Err(unsupported("BYDAY"));
+    }
+
+    Ok(rule)
+}
+
+/// The busy periods of a FREEBUSY property, each `<start>/<end>` or `<start>/<duration>`.
+fn parse_free_busy(property: &Property, zones: &Timezones) -> Result<Vec<Block>, IcsError> {
+    if property
+        .param("FBTYPE")
+        .is_some_and(|fbtype| fbtype.eq_ignore_ascii_case("FREE"))
+    {
+        return Ok(Vec::new());
+    }
+
+    let mut blocks = Vec::new();
+
+    for period in property.value.split(',') {
+        let (start, end) = match period.split_once('/') {
+            Some(period) => period,
+            None => {
+                return Err(IcsError::InvalidDateTime {
+                    line: property.line,
+                    value: period.to_string(),
+                })
+            }
+        };
+
+        let (start, _) = parse_moment(property, start, zones)?;
+        let duration = if end.trim_start().starts_with(['P', '+', '-']) {
+            parse_duration(property.line, end)?
+        } else {
+            parse_moment(property, end, zones)?.0.to_utc(Tz::UTC) - start.to_utc(Tz::UTC)
+        };
+
+        blocks.push(Block {
+            uid: None,
+            start,
+            duration,
+            rule: None,
+            exceptions: Vec::new(),
+        });
+    }
+
+    Ok(blocks)
+}
+
+/// Whether `error` only means a single event can't be read, rather than that the file is broken.
+fn is_unsupported(error: &IcsError) -> bool {
+    matches!(
+        error,
+        IcsError::UnsupportedRule { .. } | IcsError::UnknownTimezone { .. }
+    )
+}
+
+/// The zone of a TZID: an IANA name, possibly behind a prefix such as
+/// `/mozilla.org/20050126_1/`, a zone defined by the file, or a Windows zone name.
+fn resolve_timezone(tzid: &str, zones: &Timezones) -> Option<Tz> {
+    let iana = |tzid: &str| tzid.trim_start_matches('/').parse::<Tz>().ok();
+
+    iana(tzid)
+        .or_else(|| zones.get(tzid).copied())
+        .or_else(|| windows_timezone(tzid))
+        .or_else(|| {
+            tzid.match_indices('/')
+                .find_map(|(index, _)| iana(&tzid[index + 1..]))
+        })
+}
+
+/// The zones of the file's VTIMEZONE components, taken from their X-LIC-LOCATION, their TZID if
+/// it is a Windows zone name, or else their offset if it is a whole number of hours that never
+/// changes. Components that match none of these are left out.
+fn defined_timezones(lines: &[(usize, String)]) -> Timezones {
+    let mut zones = Timezones::new();
+    let mut components: Vec<String> = Vec::new();
+    let mut tzid: Option<String> = None;
+    let mut location: Option<Tz> = None;
+    let mut offsets: Vec<(String, String)> = Vec::new();
+
+    for (line, content) in lines {
+        let property = match parse_property(*line, content) {
+            Ok(property) => property,
+            Err(_) => continue,
+        };
+
+        match (
+            property.name.as_str(),
+            components.last().map(String::as_str),
+        ) {
+            ("BEGIN", _) => {
+                let component = property.value.to_uppercase();
+                if component == "VTIMEZONE" {
+                    tzid = None;
+                    location = None;
+                    offsets.clear();
+                }
+                components.push(component);
+            }
+            ("END", _) => {
+                if components.pop().as_deref() != Some("VTIMEZONE") {
+                    continue;
+                }
+
+                let tzid = match tzid.take() {
+                    Some(tzid) => tzid,
+                    None => continue,
+                };
+
+                let fixed = match offsets.as_slice() {
+                    [(component, offset), rest @ ..]
+                        if component == "STANDARD"
+                            && rest.iter().all(|other| other == &offsets[0]) =>
+                    {
+                        fixed_offset_timezone(offset)
+                    }
+                    _ => None,
+                };
+
+                if let Some(tz) = location
+                    .take()
+                    .or_else(|| windows_timezone(&tzid))
+                    .or(fixed)
+                {
+                    zones.insert(tzid, tz);
+                }
+            }
+            ("TZID", Some("VTIMEZONE")) => tzid = Some(property.value.to_string()),
+            ("X-LIC-LOCATION", Some("VTIMEZONE")) => location = property.value.parse().ok(),
+            ("TZOFFSETTO", Some(component @ ("STANDARD" | "DAYLIGHT"))) => {
+                offsets.push((component.to_string(), property.value.to_string()))
+            }
+            _ => {}
+        }
+    }
+
+    zones
+}
+
+/// The `Etc/GMT` zone of a UTC offset such as `-0500`, if it is a whole number of hours.
+fn fixed_offset_timezone(offset: &str) -> Option<Tz> {
+    // The sign of an Etc/GMT zone is the opposite of its offset's.
+    let (sign, digits) = match offset.trim().split_at_checked(1)? {
+        ("+", digits) => ("-", digits),
+        ("-", digits) => ("+", digits),
+        _ => return None,
+    };
+
+    let hours: u32 = digits.get(..2)?.parse().ok()?;
+
+    if !digits.get(2..)?.trim_matches('0').is_empty() {
+        None
+    } else if hours == 0 {
+        Some(Tz::UTC)
+    } else {
+        format!("Etc/GMT{}{}", sign, hours).parse().ok()
+    }
+}
+
+/// The IANA zone of a Windows zone name such as `Eastern Standard Time`, as Outlook writes them,
+/// following the CLDR mapping for each zone's main territory.
+fn windows_timezone(name: &str) -> Option<Tz> {
+    WINDOWS_TIMEZONES
+        .iter()
+        .find(|(windows, _)| windows.eq_ignore_ascii_case(name.trim()))
+        .and_then(|(_, iana)| iana.parse().ok())
+}
+
+const WINDOWS_TIMEZONES: &[(&str, &str)] = &[
+    ("Dateline Standard Time", "Etc/GMT+12"),
+    ("UTC-11", "Etc/GMT+11"),
+    ("Aleutian Standard Time", "America/Adak"),
+    ("Hawaiian Standard Time", "Pacific/Honolulu"),
+    ("Marquesas Standard Time", "Pacific/Marquesas"),
+    ("Alaskan Standard Time", "America/Anchorage"),
+    ("UTC-09", "Etc/GMT+9"),
+    ("Pacific Standard Time (Mexico)", "America/Tijuana"),
+    ("UTC-08", "Etc/GMT+8"),
+    ("Pacific Standard Time", "America/Los_Angeles"),
+    ("US Mountain Standard Time", "America/Phoenix"),
+    ("Mountain Standard Time (Mexico)", "America/Mazatlan"),
+    ("Mountain Standard Time", "America/Denver"),
+    ("Yukon Standard Time", "America/Whitehorse"),
+    ("Central America Standard Time", "America/Guatemala"),
+    ("Central Standard Time", "America/Chicago"),
+    ("Easter Island Standard Time", "Pacific/Easter"),
+    ("Central Standard Time (Mexico)", "America/Mexico_City"),
+    ("Canada Central Standard Time", "America/Regina"),
+    ("SA Pacific Standard Time", "America/Bogota"),
+    ("Eastern Standard Time (Mexico)", "America/Cancun"),
+    ("Eastern Standard Time", "America/New_York"),
+    ("Haiti Standard Time", "America/Port-au-Prince"),
+    ("Cuba Standard Time", "America/Havana"),
+    ("US Eastern Standard Time", "America/Indiana/Indianapolis"),
+    ("Turks And Caicos Standard Time", "America/Grand_Turk"),
+    ("Paraguay Standard Time", "America/Asuncion"),
+    ("Atlantic Standard Time", "America/Halifax"),
+    ("Venezuela Standard Time", "America/Caracas"),
+    ("Central Brazilian Standard Time", "America/Cuiaba"),
+    ("SA Western Standard Time", "America/La_Paz"),
+    ("Pacific SA Standard Time", "America/Santiago"),
+    ("Newfoundland Standard Time", "America/St_Johns"),
+    ("Tocantins Standard Time", "America/Araguaina"),
+    ("E. South America Standard Time", "America/Sao_Paulo"),
+    ("SA Eastern Standard Time", "America/Cayenne"),
+    ("Argentina Standard Time", "America/Argentina/Buenos_Aires"),
+    ("Greenland Standard Time", "America/Nuuk"),
+    ("Montevideo Standard Time", "America/Montevideo"),
+    ("Magallanes Standard Time", "America/Punta_Arenas"),
+    ("Saint Pierre Standard Time", "America/Miquelon"),
+    ("Bahia Standard Time", "America/Bahia"),
+    ("UTC-02", "Etc/GMT+2"),
+    ("Azores Standard Time", "Atlantic/Azores"),
+    ("Cape Verde Standard Time", "Atlantic/Cape_Verde"),
+    ("UTC", "Etc/UTC"),
+    ("GMT Standard Time", "Europe/London"),
+    ("Greenwich Standard Time", "Atlantic/Reykjavik"),
+    ("Sao Tome Standard Time", "Africa/Sao_Tome"),
+    ("Morocco Standard Time", "Africa/Casablanca"),
+    ("W. Europe Standard Time", "Europe/Berlin"),
+    ("Central Europe Standard Time", "Europe/Budapest"),
+    ("Romance Standard Time", "Europe/Paris"),
+    ("Central European Standard Time", "Europe/Warsaw"),
+    ("W. Central Africa Standard Time", "Africa/Lagos"),
+    ("Jordan Standard Time", "Asia/Amman"),
+    ("GTB Standard Time", "Europe/Bucharest"),
+    ("Middle East Standard Time", "Asia/Beirut"),
+    ("Egypt Standard Time", "Africa/Cairo"),
+    ("E. Europe Standard Time", "Europe/Chisinau"),
+    ("Syria Standard Time", "Asia/Damascus"),
+    ("West Bank Standard Time", "Asia/Hebron"),
+    ("South Africa Standard Time", "Africa/Johannesburg"),
+    ("FLE Standard Time", "Europe/Kiev"),
+    ("Israel Standard Time", "Asia/Jerusalem"),
+    ("South Sudan Standard Time", "Africa/Juba"),
+    ("Kaliningrad Standard Time", "Europe/Kaliningrad"),
+    ("Sudan Standard Time", "Africa/Khartoum"),
+    ("Libya Standard Time", "Africa/Tripoli"),
+    ("Namibia Standard Time", "Africa/Windhoek"),
+    ("Arabic Standard Time", "Asia/Baghdad"),
+    ("Turkey Standard Time", "Europe/Istanbul"),
+    ("Arab Standard Time", "Asia/Riyadh"),
+    ("Belarus Standard Time", "Europe/Minsk"),
+    ("Russian Standard Time", "Europe/Moscow"),
+    ("E. Africa Standard Time", "Africa/Nairobi"),
+    ("Volgograd Standard Time", "Europe/Volgograd"),
+    ("Iran Standard Time", "Asia/Tehran"),
+    ("Arabian Standard Time", "Asia/Dubai"),
+    ("Astrakhan Standard Time", "Europe/Astrakhan"),
+    ("Azerbaijan Standard Time", "Asia/Baku"),
+    ("Russia Time Zone 3", "Europe/Samara"),
+    ("Mauritius Standard Time", "Indian/Mauritius"),
+    ("Saratov Standard Time", "Europe/Saratov"),
+    ("Georgian Standard Time", "Asia/Tbilisi"),
+    ("Caucasus Standard Time", "Asia/Yerevan"),
+    ("Afghanistan Standard Time", "Asia/Kabul"),
+    ("West Asia Standard Time", "Asia/Tashkent"),
+    ("Ekaterinburg Standard Time", "Asia/Yekaterinburg"),
+    ("Pakistan Standard Time", "Asia/Karachi"),
+    ("Qyzylorda Standard Time", "Asia/Qyzylorda"),
+    ("India Standard Time", "Asia/Kolkata"),
+    ("Sri Lanka Standard Time", "Asia/Colombo"),
+    ("Nepal Standard Time", "Asia/Kathmandu"),
+    ("Central Asia Standard Time", "Asia/Almaty"),
+    ("Bangladesh Standard Time", "Asia/Dhaka"),
+    ("Omsk Standard Time", "Asia/Omsk"),
+    ("Myanmar Standard Time", "Asia/Yangon"),
+    ("SE Asia Standard Time", "Asia/Bangkok"),
+    ("Altai Standard Time", "Asia/Barnaul"),
+    ("W. Mongolia Standard Time", "Asia/Hovd"),
+    ("North Asia Standard Time", "Asia/Krasnoyarsk"),
+    ("N. Central Asia Standard Time", "Asia/Novosibirsk"),
+    ("Tomsk Standard Time", "Asia/Tomsk"),
+    ("China Standard Time", "Asia/Shanghai"),
+    ("North Asia East Standard Time", "Asia/Irkutsk"),
+    ("Singapore Standard Time", "Asia/Singapore"),
+    ("W. Australia Standard Time", "Australia/Perth"),
+    ("Taipei Standard Time", "Asia/Taipei"),
+    ("Ulaanbaatar Standard Time", "Asia/Ulaanbaatar"),
+    ("Aus Central W. Standard Time", "Australia/Eucla"),
+    ("Transbaikal Standard Time", "Asia/Chita"),
+    ("Tokyo Standard Time", "Asia/Tokyo"),
+    ("North Korea Standard Time", "Asia/Pyongyang"),
+    ("Korea Standard Time", "Asia/Seoul"),
+    ("Yakutsk Standard Time", "Asia/Yakutsk"),
+    ("Cen. Australia Standard Time", "Australia/Adelaide"),
+    ("AUS Central Standard Time", "Australia/Darwin"),
+    ("E. Australia Standard Time", "Australia/Brisbane"),
+    ("AUS Eastern Standard Time", "Australia/Sydney"),
+    ("West Pacific Standard Time", "Pacific/Port_Moresby"),
+    ("Tasmania Standard Time", "Australia/Hobart"),
+    ("Vladivostok Standard Time", "Asia/Vladivostok"),
+    ("Lord Howe Standard Time", "Australia/Lord_Howe"),
+    ("Bougainville Standard Time", "Pacific/Bougainville"),
+    ("Russia Time Zone 10", "Asia/Srednekolymsk"),
+    ("Magadan Standard Time", "Asia/Magadan"),
+    ("Norfolk Standard Time", "Pacific/Norfolk"),
+    ("Sakhalin Standard Time", "Asia/Sakhalin"),
+    ("Central Pacific Standard Time", "Pacific/Guadalcanal"),
+    ("Russia Time Zone 11", "Asia/Kamchatka"),
+    ("New Zealand Standard Time", "Pacific/Auckland"),
+    ("UTC+12", "Etc/GMT-12"),
+    ("Fiji Standard Time", "Pacific/Fiji"),
+    ("Chatham Islands Standard Time", "Pacific/Chatham"),
+    ("UTC+13", "Etc/GMT-13"),
+    ("Tonga Standard Time", "Pacific/Tongatapu"),
+    ("Samoa Standard Time", "Pacific/Apia"),
+    ("Line Islands Standard Time", "Pacific/Kiritimati"),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::errors::IcsError;
+    use crate::fetch_availability::ics::{
+        parse_duration, windows_timezone, Calendar, Period, WINDOWS_TIMEZONES,
+    };
+    use chrono::{DateTime, Duration, Utc};
+    use chrono_tz::Tz;
+    use claims::{assert_err, assert_ok};
+
+    fn time(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn calendar(body: &str) -> Result<Calendar, IcsError> {
+        Calendar::parse(&format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n",
+            body.replace('\n', "\r\n")
+        ))
+    }
+
+    fn busy(calendar: &Calendar, from: &str, to: &str) -> Vec<Period> {
+        calendar.busy(time(from), time(to), Tz::UTC)
+    }
+
+    #[test]
+    fn test_parse_event_with_time_zone() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nUID:1\nSUMMARY:Standup\nDTSTART;TZID=America/New_York:20230903T091500\nDTEND;TZID=America/New_York:20230903T093000\nEND:VEVENT\n",
+        );
+
+        assert_ok!(&calendar);
+
+        assert_eq!(
+            busy(
+                &calendar.unwrap(),
+                "2023-09-03T00:00:00Z",
+                "2023-09-04T00:00:00Z"
+            ),
+            vec![(time("2023-09-03T13:15:00Z"), time("2023-09-03T13:30:00Z"))]
+        );
+    }
+
+    #[test]
+    fn test_parse_unfolds_lines_and_skips_free_events() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nDTSTART:20230903T130000Z\nDESCRIPTION:A long descr\n iption\nDURATION:PT1H\nTRANSP:TRANSPARENT\nEND:VEVENT\nBEGIN:VEVENT\nDTSTART:20230903T150000Z\nDURATION:PT1H\nSTATUS:CANCELLED\nEND:VEVENT\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            busy(&calendar, "2023-09-03T00:00:00Z", "2023-09-04T00:00:00Z"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_weekly_rule_across_daylight_saving() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nDTSTART;TZID=America/New_York:20231023T090000\nDURATION:PT30M\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=5\nEND:VEVENT\n",
+        )
+        .unwrap();
+
+        let busy = busy(&calendar, "2023-10-01T00:00:00Z", "2023-12-01T00:00:00Z");
+
+        // Clocks in New York went back on November 5th.
+        assert_eq!(
+            busy.iter().map(|(start, _)| *start).collect::<Vec<_>>(),
+            vec![
+                time("2023-10-23T13:00:00Z"),
+                time("2023-10-25T13:00:00Z"),
+                time("2023-10-30T13:00:00Z"),
+                time("2023-11-01T13:00:00Z"),
+                time("2023-11-06T14:00:00Z"),
+            ]
+        );
+        assert_eq!(busy[4].1 - busy[4].0, Duration::minutes(30));
+    }
+
+    #[test]
+    fn test_rule_until_exceptions_and_overrides() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nUID:daily\nDTSTART:20230904T120000Z\nDTEND:20230904T130000Z\nRRULE:FREQ=DAILY;INTERVAL=2;UNTIL=20230912T120000Z\nEXDATE:20230906T120000Z\nEND:VEVENT\nBEGIN:VEVENT\nUID:daily\nRECURRENCE-ID:20230908T120000Z\nDTSTART:20230908T160000Z\nDTEND:20230908T170000Z\nEND:VEVENT\n",
+        )
+        .unwrap();
+
+        let mut busy = busy(&calendar, "2023-09-01T00:00:00Z", "2023-10-01T00:00:00Z");
+        busy.sort();
+
+        assert_eq!(
+            busy.iter().map(|(start, _)| *start).collect::<Vec<_>>(),
+            vec![
+                time("2023-09-04T12:00:00Z"),
+                time("2023-09-08T16:00:00Z"),
+                time("2023-09-10T12:00:00Z"),
+                time("2023-09-12T12:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monthly_rule_skips_short_months() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nDTSTART:20230131T100000Z\nDURATION:PT1H\nRRULE:FREQ=MONTHLY\nEND:VEVENT\n",
+        )
+        .unwrap();
+
+        let busy = busy(&calendar, "2023-01-01T00:00:00Z", "2023-06-01T00:00:00Z");
+
+        assert_eq!(
+            busy.iter().map(|(start, _)| *start).collect::<Vec<_>>(),
+            vec![
+                time("2023-01-31T10:00:00Z"),
+                time("2023-03-31T10:00:00Z"),
+                time("2023-05-31T10:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_all_day_event_is_floating() {
+        let calendar = calendar("BEGIN:VEVENT\nDTSTART;VALUE=DATE:20230903\nEND:VEVENT\n").unwrap();
+
+        let busy = calendar.busy(
+            time("2023-09-01T00:00:00Z"),
+            time("2023-09-05T00:00:00Z"),
+            "America/New_York".parse().unwrap(),
+        );
+
+        assert_eq!(
+            busy,
+            vec![(time("2023-09-03T04:00:00Z"), time("2023-09-04T04:00:00Z"))]
+        );
+    }
+
+    #[test]
+    fn test_free_busy() {
+        let calendar = calendar(
+            "BEGIN:VFREEBUSY\nFREEBUSY;FBTYPE=BUSY:20230903T130000Z/20230903T131500Z,20230903T140000Z/PT30M\nFREEBUSY;FBTYPE=FREE:20230903T150000Z/PT1H\nEND:VFREEBUSY\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            busy(&calendar, "2023-09-03T00:00:00Z", "2023-09-04T00:00:00Z"),
+            vec![
+                (time("2023-09-03T13:00:00Z"), time("2023-09-03T13:15:00Z")),
+                (time("2023-09-03T14:00:00Z"), time("2023-09-03T14:30:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration(1, "PT1H30M").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration(1, "P1W").unwrap(), Duration::weeks(1));
+        assert_eq!(parse_duration(1, "P1DT12H").unwrap(), Duration::hours(36));
+        assert_eq!(parse_duration(1, "-PT15M").unwrap(), Duration::minutes(-15));
+        assert_err!(parse_duration(1, "1H"));
+        assert_err!(parse_duration(1, "PT1H30"));
+        assert_err!(parse_duration(1, "P99999999999999W"));
+        assert_err!(parse_duration(1, "P99999999999999999999D"));
+        assert_err!(parse_duration(1, "P15250284452W15250284452W"));
+    }
+
+    #[test]
+    fn test_parse_duration_past_the_end_of_time() {
+        assert!(matches!(
+            calendar(
+                "BEGIN:VEVENT\nDTSTART:20230903T091500Z\nDURATION:P15250284452W\nEND:VEVENT\n"
+            )
+            .unwrap_err(),
+            IcsError::InvalidDuration { line: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            Calendar::parse("BEGIN:VEVENT\nEND:VEVENT\n").unwrap_err(),
+            IcsError::NotACalendar
+        ));
+        assert!(matches!(
+            calendar("BEGIN:VEVENT\nDTSTART:2023-09-03\nEND:VEVENT\n").unwrap_err(),
+            IcsError::InvalidDateTime { line: 4, .. }
+        ));
+        assert!(matches!(
+            calendar("BEGIN:VEVENT\nSUMMARY:No start\nEND:VEVENT\n").unwrap_err(),
+            IcsError::MissingStart { line: 3 }
+        ));
+    }
+
+    #[test]
+    fn test_parse_skips_unsupported_events() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nDTSTART;TZID=Nowhere/Special:20230903T091500\nDURATION:PT1H\nEND:VEVENT\nBEGIN:VEVENT\nDTSTART:20230903T091500Z\nDURATION:PT1H\nRRULE:FREQ=MONTHLY;BYDAY=1MO\nEND:VEVENT\nBEGIN:VEVENT\nDTSTART:20230903T091500Z\nDURATION:PT1H\nRRULE:FREQ=YEARLY;BYMONTH=9;BYMONTHDAY=3\nEND:VEVENT\nBEGIN:VEVENT\nDTSTART:20230903T130000Z\nDURATION:PT15M\nEND:VEVENT\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            busy(&calendar, "2023-09-03T00:00:00Z", "2023-09-04T00:00:00Z"),
+            vec![(time("2023-09-03T13:00:00Z"), time("2023-09-03T13:15:00Z"))]
+        );
+    }
+
+    #[test]
+    fn test_parse_resolves_time_zones() {
+        let calendar = calendar(
+            "BEGIN:VTIMEZONE\nTZID:Eastern\nX-LIC-LOCATION:America/New_York\nEND:VTIMEZONE\nBEGIN:VTIMEZONE\nTZID:Custom Fixed\nBEGIN:STANDARD\nDTSTART:16010101T000000\nTZOFFSETFROM:+0200\nTZOFFSETTO:+0200\nEND:STANDARD\nEND:VTIMEZONE\nBEGIN:VEVENT\nDTSTART;TZID=Eastern:20230903T090000\nDURATION:PT15M\nEND:VEVENT\nBEGIN:VEVENT\nDTSTART;TZID=\"Eastern Standard Time\":20230903T100000\nDURATION:PT15M\nEND:VEVENT\nBEGIN:VEVENT\nDTSTART;TZID=/mozilla.org/20050126_1/America/New_York:20230903T110000\nDURATION:PT15M\nEND:VEVENT\nBEGIN:VEVENT\nDTSTART;TZID=Custom Fixed:20230903T180000\nDURATION:PT15M\nEND:VEVENT\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            busy(&calendar, "2023-09-03T00:00:00Z", "2023-09-04T00:00:00Z"),
+            vec![
+                (time("2023-09-03T13:00:00Z"), time("2023-09-03T13:15:00Z")),
+                (time("2023-09-03T14:00:00Z"), time("2023-09-03T14:15:00Z")),
+                (time("2023-09-03T15:00:00Z"), time("2023-09-03T15:15:00Z")),
+                (time("2023-09-03T16:00:00Z"), time("2023-09-03T16:15:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_windows_timezones_are_known() {
+        for (windows, _) in WINDOWS_TIMEZONES {
+            assert!(windows_timezone(windows).is_some(), "{}", windows);
+        }
+    }
+}
@@ -0,0 +1,480 @@
+use crate::fetch_availability::errors::{ImportError, ParseWhen2MeetError, ProcessResultError};
+use crate::fetch_availability::model::{
+    find_unrepresentable_slot, Event, EventMetadata, EventMode, Person,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+
+/// The JSON import schema: everyone who responded, and who is available in each slot.
+///
+/// ```json
+/// {
+///   "title": "Team Sync",
+///   "timezone": "America/New_York",
+///   "mode": "specific_dates",
+///   "people": ["Muneer", "Brian", "Garrett"],
+///   "slots": [
+///     { "start": "2023-09-03T13:00:00Z", "available": [] },
+///     { "start": 1693746900, "available": ["Muneer"] }
+///   ]
+/// }
+/// ```
+///
+/// `start` is an RFC 3339 date-time or unix seconds. `title`, `timezone` and `mode` are optional;
+/// with a `mode` of `days_of_the_week`, only the weekday and time of day of each start count.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AvailabilityJson {
+    pub title: Option<Box<str>>,
+    pub timezone: Option<Box<str>>,
+    pub mode: Option<EventMode>,
+    pub people: Vec<Box<str>>,
+    pub slots: Vec<SlotJson>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SlotJson {
+    pub start: TimestampJson,
+    #[serde(default)]
+    pub available: Vec<Box<str>>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum TimestampJson {
+    Unix(i64),
+    Text(String),
+}
+
+/// Reads a grid with a `timestamp` column followed by one column per person, e.g.
+///
+/// ```csv
+/// timestamp,Muneer,Brian
+/// 2023-09-03T13:00:00Z,1,0
+/// 2023-09-03T13:15:00Z,yes,x
+/// ```
+///
+/// Timestamps are RFC 3339 date-times or unix seconds, and always specific dates. A cell of `1`,
+/// `x`, `yes`, `y` or `true` means available; `0`, `no`, `n`, `false` or an empty cell means
+/// unavailable.
+pub fn parse_availability_csv(csv: &str) -> Result<Event, ParseWhen2MeetError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(source) => return Err(ParseWhen2MeetError::Import(ImportError::Csv(source))),
+    };
+
+    let mut columns = headers.iter();
+
+    match columns.next() {
+        Some(column) if column.eq_ignore_ascii_case("timestamp") => {}
+        _ => {
+            return Err(ParseWhen2MeetError::Import(
+                ImportError::MissingTimestampColumn,
+            ))
+        }
+    }
+
+    let people = people_names(
+        columns
+            .map(|name| name.to_string().into_boxed_str())
+            .collect(),
+    )?;
+
+    let mut rows = Vec::new();
+
+    for (row, record) in reader.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(source) => return Err(ParseWhen2MeetError::Import(ImportError::Csv(source))),
+        };
+
+        let mut cells = record.iter();
+
+        let start_time = parse_timestamp(row, cells.next().unwrap_or_default())?;
+
+        let mut available = Vec::with_capacity(people.len());
+
+        for (name, cell) in people.iter().zip(cells) {
+            available.push(match cell.to_lowercase().as_str() {
+                "1" | "x" | "yes" | "y" | "true" => true,
+                "0" | "" | "no" | "n" | "false" => false,
+                _ => {
+                    return Err(ParseWhen2MeetError::Import(ImportError::InvalidCell {
+                        row,
+                        person: name.to_string(),
+                        value: cell.to_string(),
+                    }))
+                }
+            });
+        }
+
+        rows.push((start_time, available));
+    }
+
+    build_event(
+        people,
+        rows,
+        EventMode::SpecificDates,
+        EventMetadata::default(),
+    )
+}
+
+/// Reads availability in the `AvailabilityJson` schema.
+pub fn parse_availability_json(json: &str) -> Result<Event, ParseWhen2MeetError> {
+    let availability: AvailabilityJson = match serde_json::from_str(json) {
+        Ok(availability) => availability,
+        Err(source) => return Err(ParseWhen2MeetError::Import(ImportError::Json(source))),
+    };
+
+    let people = people_names(availability.people)?;
+
+    let mut rows = Vec::with_capacity(availability.slots.len());
+
+    for (row, slot) in availability.slots.iter().enumerate() {
+        let start_time = match &slot.start {
+            TimestampJson::Unix(timestamp) => parse_timestamp(row, &timestamp.to_string())?,
+            TimestampJson::Text(timestamp) => parse_timestamp(row, timestamp)?,
+        };
+
+        if let Some(name) = slot
+            .available
+            .iter()
+            .find(|name| !people.iter().any(|person| **person == *name.trim()))
+        {
+            return Err(ParseWhen2MeetError::Import(ImportError::UnknownPerson {
+                row,
+                name: name.to_string(),
+            }));
+        }
+
+        let available = people
+            .iter()
+            .map(|person| slot.available.iter().any(|name| name.trim() == &**person))
+            .collect();
+
+        rows.push((start_time, available));
+    }
+
+    build_event(
+        people,
+        rows,
+        availability.mode.unwrap_or(EventMode::SpecificDates),
+        EventMetadata {
+            title: availability.title,
+            timezone: availability.timezone,
+        },
+    )
+}
+
+fn people_names(names: Vec<Box<str>>) -> Result<Vec<Box<str>>, ParseWhen2MeetError> {
+    let mut people: Vec<Box<str>> = Vec::with_capacity(names.len());
+
+    for name in names {
+        let name = name.trim();
+
+        if name.is_empty() || people.iter().any(|person| **person == *name) {
+            return Err(ParseWhen2MeetError::Import(ImportError::DuplicatePerson {
+                name: name.to_string(),
+            }));
+        }
+
+        people.push(name.to_string().into_boxed_str());
+    }
+
+    Ok(people)
+}
+
+fn parse_timestamp(row: usize, timestamp: &str) -> Result<DateTime<Utc>, ParseWhen2MeetError> {
+    let start_time = match timestamp.parse::<i64>() {
+        Ok(timestamp) => Utc.timestamp_opt(timestamp, 0).single(),
+        Err(_) => DateTime::parse_from_rfc3339(timestamp)
+            .ok()
+            .map(|start_time| start_time.with_timezone(&Utc)),
+    };
+
+    match start_time {
+        Some(start_time) => Ok(start_time),
+        None => Err(ParseWhen2MeetError::Import(ImportError::InvalidTimestamp {
+            row,
+            timestamp: timestamp.to_string(),
+        })),
+    }
+}
+
+/// Builds the event the same way a fetched one is, with every person's name doubling as their ID,
+/// but with the mode given rather than guessed from when2meet's reference week.
+fn build_event(
+    people: Vec<Box<str>>,
+    rows: Vec<(DateTime<Utc>, Vec<bool>)>,
+    mode: EventMode,
+    metadata: EventMetadata,
+) -> Result<Event, ParseWhen2MeetError> {
+    for (row, pair) in rows.windows(2).enumerate() {
+        if pair[1].0 <= pair[0].0 {
+            return Err(ParseWhen2MeetError::ProcessResult(
+                ProcessResultError::AvailMatrixTimestampNotIncreasing {
+                    row: row + 1,
+                    timestamp: pair[1].0.to_rfc3339(),
+                },
+            ));
+        }
+    }
+
+    let start_times: Vec<DateTime<Utc>> = rows.iter().map(|(start_time, _)| *start_time).collect();
+
+    if let Some(row) = find_unrepresentable_slot(&start_times) {
+        return Err(ParseWhen2MeetError::Import(
+            ImportError::TimestampOutOfRange {
+                row,
+                timestamp: start_times[row].to_rfc3339(),
+            },
+        ));
+    }
+
+    let rows = rows
+        .into_iter()
+        .map(|(start_time, available)| {
+            let people = people
+                .iter()
+                .zip(available)
+                .map(|(name, available)| Person {
+                    id: name.clone(),
+                    name: name.clone(),
+                    available,
+                })
+                .collect();
+
+            (start_time, people)
+        })
+        .collect();
+
+    Ok(Event::with_mode(rows, mode).with_metadata(metadata))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::errors::{ImportError, ParseWhen2MeetError, ProcessResultError};
+    use crate::fetch_availability::html::parse_when2meet_html;
+    use crate::fetch_availability::import::{parse_availability_csv, parse_availability_json};
+    use crate::fetch_availability::model::{Event, EventMode, Recurrence};
+    use claims::{assert_err, assert_ok};
+
+    const HTML_FIXTURE_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/when2meet_event.html"
+    );
+
+    const CSV_FIXTURE_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/availability.csv"
+    );
+
+    const JSON_FIXTURE_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/availability.json"
+    );
+
+    fn slot_availability(event: &Event) -> Vec<Vec<bool>> {
+        event
+            .slots
+            .iter()
+            .map(|slot| slot.people.iter().map(|person| person.available).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_availability_csv_matches_html() {
+        let event = parse_availability_csv(&std::fs::read_to_string(CSV_FIXTURE_PATH).unwrap());
+
+        assert_ok!(&event);
+
+        let event = event.unwrap();
+        let html_event =
+            parse_when2meet_html(&std::fs::read_to_string(HTML_FIXTURE_PATH).unwrap()).unwrap();
+
+        assert_eq!(event.title, None);
+        assert_eq!(event.slot_duration, html_event.slot_duration);
+        assert_eq!(slot_availability(&event), slot_availability(&html_event));
+        assert_eq!(
+            event
+                .slots
+                .iter()
+                .map(|slot| slot.start_time)
+                .collect::<Vec<_>>(),
+            html_event
+                .slots
+                .iter()
+                .map(|slot| slot.start_time)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse_availability_json_matches_html() {
+        let event = parse_availability_json(&std::fs::read_to_string(JSON_FIXTURE_PATH).unwrap());
+
+        assert_ok!(&event);
+
+        let event = event.unwrap();
+        let html_event =
+            parse_when2meet_html(&std::fs::read_to_string(HTML_FIXTURE_PATH).unwrap()).unwrap();
+
+        assert_eq!(event.title, html_event.title);
+        assert_eq!(event.timezone, html_event.timezone);
+        assert_eq!(slot_availability(&event), slot_availability(&html_event));
+    }
+
+    #[test]
+    fn test_parse_availability_csv_before_1980_is_specific_dates() {
+        let event = parse_availability_csv("timestamp,Muneer\n1975-06-02T13:00:00Z,1\n");
+
+        assert_ok!(&event);
+
+        let event = event.unwrap();
+
+        assert_eq!(event.mode, EventMode::SpecificDates);
+        assert_eq!(event.slots[0].recurrence, Recurrence::Once);
+    }
+
+    #[test]
+    fn test_parse_availability_json_mode() {
+        let json = |mode: &str| {
+            format!(
+                r#"{{{}"people": ["Muneer"], "slots": [{{"start": "1975-06-02T13:00:00Z", "available": ["Muneer"]}}]}}"#,
+                mode
+            )
+        };
+
+        let specific_dates = parse_availability_json(&json("")).unwrap();
+        let days_of_the_week =
+            parse_availability_json(&json(r#""mode": "days_of_the_week", "#)).unwrap();
+
+        assert_eq!(specific_dates.mode, EventMode::SpecificDates);
+        assert_eq!(days_of_the_week.mode, EventMode::DaysOfTheWeek);
+        assert_eq!(days_of_the_week.slots[0].recurrence, Recurrence::Weekly);
+    }
+
+    #[test]
+    fn test_parse_availability_csv_cells() {
+        let event =
+            parse_availability_csv("timestamp,Muneer,Brian\n1693746000,x,\n1693746900,Yes,TRUE\n");
+
+        assert_ok!(&event);
+
+        assert_eq!(
+            slot_availability(&event.unwrap()),
+            vec![vec![true, false], vec![true, true]]
+        );
+    }
+
+    #[test]
+    fn test_parse_availability_csv_invalid_cell() {
+        let event = parse_availability_csv("timestamp,Muneer\n1693746000,maybe\n");
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::Import(ImportError::InvalidCell { row: 0, person, value })
+                if person == "Muneer" && value == "maybe"
+        ));
+    }
+
+    #[test]
+    fn test_parse_availability_csv_missing_timestamp_column() {
+        let event = parse_availability_csv("Muneer,Brian\n1,0\n");
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::Import(ImportError::MissingTimestampColumn)
+        ));
+    }
+
+    #[test]
+    fn test_parse_availability_csv_duplicate_person() {
+        let event = parse_availability_csv("timestamp,Muneer,Muneer\n1693746000,1,0\n");
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::Import(ImportError::DuplicatePerson { name }) if name == "Muneer"
+        ));
+    }
+
+    #[test]
+    fn test_parse_availability_csv_timestamp_not_increasing() {
+        let event = parse_availability_csv("timestamp,Muneer\n1693746900,1\n1693746000,1\n");
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::ProcessResult(
+                ProcessResultError::AvailMatrixTimestampNotIncreasing { row: 1, .. }
+            )
+        ));
+    }
+
+    #[test]
+    fn test_parse_availability_json_invalid_timestamp() {
+        let event = parse_availability_json(
+            r#"{"people": ["Muneer"], "slots": [{"start": "Sunday 1pm", "available": []}]}"#,
+        );
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::Import(ImportError::InvalidTimestamp { row: 0, timestamp })
+                if timestamp == "Sunday 1pm"
+        ));
+    }
+
+    #[test]
+    fn test_parse_availability_csv_timestamp_out_of_range() {
+        let event = parse_availability_csv("timestamp,Muneer\n1693746000,1\n8210298412799,1\n");
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::Import(ImportError::TimestampOutOfRange { row: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_availability_json_timestamp_out_of_range() {
+        let event = parse_availability_json(
+            r#"{"people": ["Muneer"], "slots": [{"start": 8210298412799, "available": []}]}"#,
+        );
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::Import(ImportError::TimestampOutOfRange { row: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_availability_json_unknown_person() {
+        let event = parse_availability_json(
+            r#"{"people": ["Muneer"], "slots": [{"start": 1693746000, "available": ["Brian"]}]}"#,
+        );
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::Import(ImportError::UnknownPerson { row: 0, name }) if name == "Brian"
+        ));
+    }
+}
@@ -1,6 +1,34 @@
+pub mod aliases;
+pub mod cache;
+pub mod caldav;
+pub mod chrome;
+pub mod crabfit;
 pub mod errors;
+pub mod html;
+pub mod ics;
+pub mod import;
 pub mod model;
 pub mod parse;
+pub mod retry;
+pub mod snapshot;
+pub mod source;
+pub mod validate;
 
-pub use model::{fold, Person, Slot};
-pub use parse::parse_when2meet;
+pub use aliases::Aliases;
+pub use cache::{Cache, CacheMode, CacheOptions, CachedSource};
+pub use caldav::{CalDavClient, CalDavOptions};
+pub use chrome::ChromeOptions;
+pub use crabfit::{parse_crabfit_event_id, CrabFitOptions, CrabFitSource};
+pub use html::{
+    parse_when2meet_html, parse_when2meet_http, snapshot_when2meet_html, snapshot_when2meet_http,
+};
+pub use ics::Calendar;
+pub use import::{parse_availability_csv, parse_availability_json, AvailabilityJson};
+pub use model::{detect_slot_duration, fold, Event, Person, Slot};
+pub use parse::{parse_when2meet, snapshot_when2meet};
+pub use retry::{Deadline, RetryPolicy};
+pub use snapshot::{Snapshot, SnapshotSource};
+pub use source::{
+    AvailabilitySource, ChromeSource, CsvSource, FileSource, HttpSource, JsonSource, MergedSource,
+};
+pub use validate::{parse_when2meet_url, validate_when2meet_url};
@@ -1,28 +1,542 @@
-use chrono::{DateTime, Duration, TimeZone, Utc};
+use crate::fetch_availability::aliases::Aliases;
+use crate::fetch_availability::errors::ProcessResultError;
+use crate::fetch_availability::ics::{Calendar, Period};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc, Weekday};
 use chrono_tz::{OffsetName, Tz};
 use iana_time_zone::get_timezone;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
+/// An event's metadata and availability grid, where every slot spans `slot_duration`.
+#[derive(Debug, PartialEq)]
+pub struct Event {
+    pub title: Option<Box<str>>,
+    pub timezone: Option<Tz>,
+    pub mode: EventMode,
+    pub participants: Vec<Participant>,
+    pub slot_duration: Duration,
+    pub slots: Vec<Slot>,
+}
+
+/// Whether the event was created for specific dates or for days of the week.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventMode {
+    SpecificDates,
+    DaysOfTheWeek,
+}
+
+/// Someone who has responded to the event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Participant {
+    pub id: Box<str>,
+    pub name: Box<str>,
+}
+
+/// The metadata read off an event page, before it is attached to an `Event`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventMetadata {
+    pub title: Option<Box<str>>,
+    pub timezone: Option<Box<str>>,
+}
+
+/// A block of the grid. For a weekly slot, `start_time` and `end_time` fall in when2meet's
+/// reference week and only their weekday and time of day are meaningful.
 #[derive(Debug, PartialEq)]
 pub struct Slot {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
+    pub recurrence: Recurrence,
     pub people: Vec<Person>,
 }
 
-#[derive(Debug, PartialEq)]
+/// Whether a slot happens once on its date or every week on its weekday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recurrence {
+    Once,
+    Weekly,
+}
+
+/// A participant in a slot. `id` identifies the person; `name` is only for display and may be
+/// shared by several people.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Person {
+    pub id: Box<str>,
     pub name: Box<str>,
     pub available: bool,
 }
 
+/// An event's own person IDs mapped to the IDs they have in a merged event.
+type MergedIds = HashMap<Box<str>, Box<str>>;
+
+impl Event {
+    /// Builds the grid of a when2meet event, telling a days of the week event apart by its slots
+    /// falling in when2meet's reference week. See `with_mode`.
+    pub fn new(rows: Vec<(DateTime<Utc>, Vec<Person>)>) -> Self {
+        let start_times: Vec<DateTime<Utc>> =
+            rows.iter().map(|(start_time, _)| *start_time).collect();
+        let mode = EventMode::detect(&start_times);
+
+        Self::with_mode(rows, mode)
+    }
+
+    /// Builds the grid from each slot's start time and people, detecting the slot duration from
+    /// the spacing of the start times and folding adjacent slots with the same people.
+    pub fn with_mode(rows: Vec<(DateTime<Utc>, Vec<Person>)>, mode: EventMode) -> Self {
+        let start_times: Vec<DateTime<Utc>> =
+            rows.iter().map(|(start_time, _)| *start_time).collect();
+        let slot_duration = detect_slot_duration(&start_times);
+
+        let mut participants: Vec<Participant> = Vec::new();
+        for (_, people) in &rows {
+            for person in people {
+                if !participants
+                    .iter()
+                    .any(|participant| participant.id == person.id)
+                {
+                    participants.push(Participant {
+                        id: person.id.clone(),
+                        name: person.name.clone(),
+                    });
+                }
+            }
+        }
+
+        let recurrence = match mode {
+            EventMode::SpecificDates => Recurrence::Once,
+            EventMode::DaysOfTheWeek => Recurrence::Weekly,
+        };
+
+        let slots = rows
+            .into_iter()
+            .map(|(start_time, people)| Slot {
+                recurrence,
+                ..Slot::new(start_time, slot_duration, people)
+            })
+            .collect();
+
+        Self {
+            title: None,
+            timezone: None,
+            mode,
+            participants,
+            slot_duration,
+            slots: fold(slots),
+        }
+    }
+
+    pub fn with_metadata(mut self, metadata: EventMetadata) -> Self {
+        self.title = metadata.title;
+        self.timezone = metadata.timezone.and_then(|timezone| timezone.parse().ok());
+        self
+    }
+
+    /// Combines several events, e.g. one poll split into a link per week, into a single grid.
+    ///
+    /// when2meet IDs are only unique within an event, so people in different events are taken to
+    /// be the same person when their names match exactly and unambiguously: a name that more than
+    /// one person has in any of the events is never merged. Each person's ID is prefixed with the
+    /// index of the event they first appear in, e.g. `0:94102857`. Slots are aligned by time, and
+    /// where events overlap a person is available if any of them says so.
+    /// Everyone is listed in every slot, as unavailable where they didn't respond to the event.
+    ///
+    /// Fails if some events are for specific dates and others for days of the week.
+    pub fn merge(mut events: Vec<Event>) -> Result<Self, ProcessResultError> {
+        if events.len() == 1 {
+            return Ok(events.remove(0));
+        }
+
+        let mode = events
+            .first()
+            .map_or(EventMode::SpecificDates, |event| event.mode);
+
+        if events.iter().any(|event| event.mode != mode) {
+            return Err(ProcessResultError::MixedEventModes);
+        }
+
+        // A name shared by several people in any one event can't tell which of them someone in
+        // another event is, so everyone with that name is kept apart.
+        let mut shared_names: HashSet<&str> = HashSet::new();
+        for event in &events {
+            let mut names: HashSet<&str> = HashSet::new();
+            for participant in &event.participants {
+                if !names.insert(&participant.name) {
+                    shared_names.insert(&participant.name);
+                }
+            }
+        }
+
+        let mut participants: Vec<Participant> = Vec::new();
+        let mut merged_ids: Vec<MergedIds> = Vec::with_capacity(events.len());
+
+        for (index, event) in events.iter().enumerate() {
+            let mut ids: MergedIds = HashMap::new();
+
+            for participant in &event.participants {
+                let same_name = participants
+                    .iter()
+                    .find(|other| other.name == participant.name)
+                    .filter(|_| !shared_names.contains(&*participant.name));
+
+                let id = match same_name {
+                    Some(other) => other.id.clone(),
+                    None => {
+                        let id = format!("{}:{}", index, participant.id).into_boxed_str();
+                        participants.push(Participant {
+                            id: id.clone(),
+                            name: participant.name.clone(),
+                        });
+                        id
+                    }
+                };
+
+                ids.insert(participant.id.clone(), id);
+            }
+
+            merged_ids.push(ids);
+        }
+
+        let mut boundaries: Vec<DateTime<Utc>> = events
+            .iter()
+            .flat_map(|event| &event.slots)
+            .flat_map(|slot| [slot.start_time, slot.end_time])
+            .collect();
+        boundaries.sort();
+        boundaries.dedup();
+
+        let mut slots = Vec::new();
+
+        for pair in boundaries.windows(2) {
+            let (start_time, end_time) = (pair[0], pair[1]);
+
+            let covering: Vec<(&MergedIds, &Slot)> = events
+                .iter()
+                .zip(&merged_ids)
+                .flat_map(|(event, ids)| {
+                    event
+                        .slots
+                        .iter()
+                        .filter(|slot| slot.start_time <= start_time && end_time <= slot.end_time)
+                        .map(move |slot| (ids, slot))
+                })
+                .collect();
+
+            let recurrence = match covering.first() {
+                Some((_, slot)) => slot.recurrence,
+                // A gap between events.
+                None => continue,
+            };
+
+            let people = participants
+                .iter()
+                .map(|participant| Person {
+                    id: participant.id.clone(),
+                    name: participant.name.clone(),
+                    available: covering.iter().any(|(ids, slot)| {
+                        slot.people.iter().any(|person| {
+                            person.available && ids.get(&person.id) == Some(&participant.id)
+                        })
+                    }),
+                })
+                .collect();
+
+            slots.push(Slot {
+                start_time,
+                end_time,
+                recurrence,
+                people,
+            });
+        }
+
+        let titles: Vec<&str> = events
+            .iter()
+            .filter_map(|event| event.title.as_deref())
+            .collect();
+
+        Ok(Self {
+            title: if titles.is_empty() {
+                None
+            } else {
+                Some(titles.join(", ").into_boxed_str())
+            },
+            timezone: events.iter().find_map(|event| event.timezone),
+            mode,
+            participants,
+            slot_duration: events
+                .iter()
+                .map(|event| event.slot_duration)
+                .min()
+                .unwrap_or_else(|| Duration::minutes(15)),
+            slots: fold(slots),
+        })
+    }
+
+    /// Renames everyone listed in `aliases` to their canonical name, and treats everyone with the
+    /// same canonical name as one person, available wherever any of their responses is.
+    pub fn with_aliases(mut self, aliases: &Aliases) -> Self {
+        let mut canonical_ids: HashMap<Box<str>, Box<str>> = HashMap::new();
+        let mut renamed: HashMap<Box<str>, Participant> = HashMap::new();
+        let mut participants: Vec<Participant> = Vec::with_capacity(self.participants.len());
+
+        for participant in self.participants {
+            let canonical = match aliases.canonical(&participant.name) {
+                Some(canonical) => canonical.to_string().into_boxed_str(),
+                None => {
+                    participants.push(participant);
+                    continue;
+                }
+            };
+
+            let id = canonical_ids
+                .entry(canonical.clone())
+                .or_insert_with(|| participant.id.clone())
+                .clone();
+            let canonical_participant = Participant {
+                id,
+                name: canonical,
+            };
+
+            if !participants.contains(&canonical_participant) {
+                participants.push(canonical_participant.clone());
+            }
+
+            renamed.insert(participant.id, canonical_participant);
+        }
+
+        for slot in &mut self.slots {
+            let mut people: Vec<Person> = Vec::with_capacity(slot.people.len());
+
+            for mut person in std::mem::take(&mut slot.people) {
+                if let Some(participant) = renamed.get(&person.id) {
+                    person.id = participant.id.clone();
+                    person.name = participant.name.clone();
+                }
+
+                match people.iter_mut().find(|other| other.id == person.id) {
+                    Some(other) => other.available |= person.available,
+                    None => people.push(person),
+                }
+            }
+
+            slot.people = people;
+        }
+
+        self.participants = participants;
+        self.slots = fold(self.slots);
+        self
+    }
+
+    /// Adds `name`, with the ID `ics:<name>`, as someone available wherever `calendar` is free.
+    ///
+    /// A days of the week slot is checked against its next occurrence on or after `week_of`.
+    /// Calendar times without a time zone are taken to be in the event's, or else the local one.
+    pub fn with_calendar(
+        mut self,
+        name: &str,
+        calendar: &Calendar,
+        week_of: DateTime<Utc>,
+    ) -> Self {
+        let participant = Participant {
+            id: format!("ics:{}", name).into_boxed_str(),
+            name: name.to_string().into_boxed_str(),
+        };
+
+        let slots = self
+            .split_by_calendar(calendar, week_of)
+            .into_iter()
+            .map(|(mut slot, busy)| {
+                slot.people.push(Person {
+                    id: participant.id.clone(),
+                    name: participant.name.clone(),
+                    available: !busy,
+                });
+                slot
+            })
+            .collect();
+
+        self.participants.push(participant);
+        self.slots = fold(slots);
+        self
+    }
+
+    /// Drops every slot, or the part of it, during which `calendar` is busy. Slots are matched to
+    /// calendar times as in `with_calendar`.
+    pub fn without_busy(mut self, calendar: &Calendar, week_of: DateTime<Utc>) -> Self {
+        let slots = self
+            .split_by_calendar(calendar, week_of)
+            .into_iter()
+            .filter(|(_, busy)| !busy)
+            .map(|(slot, _)| slot)
+            .collect();
+
+        self.slots = fold(slots);
+        self
+    }
+
+    /// The real time a calendar has to cover to check every slot against it, if there are any.
+    pub fn calendar_range(&self, week_of: DateTime<Utc>) -> Option<Period> {
+        let tz = self.calendar_timezone();
+        let periods: Vec<Period> = self
+            .slots
+            .iter()
+            .map(|slot| slot.calendar_period(tz, week_of))
+            .collect();
+
+        Some((
+            periods.iter().map(|(start, _)| *start).min()?,
+            periods.iter().map(|(_, end)| *end).max()?,
+        ))
+    }
+
+    /// The event's time zone, or else the local one.
+    pub(crate) fn calendar_timezone(&self) -> Tz {
+        self.timezone
+            .or_else(|| get_timezone().ok()?.parse().ok())
+            .unwrap_or(Tz::UTC)
+    }
+
+    /// Takes the slots, split back into single slots since `calendar` can be busy for only part
+    /// of a folded one, each with whether `calendar` is busy during it.
+    fn split_by_calendar(
+        &mut self,
+        calendar: &Calendar,
+        week_of: DateTime<Utc>,
+    ) -> Vec<(Slot, bool)> {
+        let tz = self.calendar_timezone();
+
+        let mut slots: Vec<Slot> = Vec::with_capacity(self.slots.len());
+        for slot in std::mem::take(&mut self.slots) {
+            let mut start_time = slot.start_time;
+            while start_time < slot.end_time {
+                let end_time = (start_time + self.slot_duration).min(slot.end_time);
+                slots.push(Slot {
+                    start_time,
+                    end_time,
+                    recurrence: slot.recurrence,
+                    people: slot.people.clone(),
+                });
+                start_time = end_time;
+            }
+        }
+
+        let periods: Vec<Period> = slots
+            .iter()
+            .map(|slot| slot.calendar_period(tz, week_of))
+            .collect();
+
+        let busy = match (
+            periods.iter().map(|(start, _)| *start).min(),
+            periods.iter().map(|(_, end)| *end).max(),
+        ) {
+            (Some(from), Some(to)) => calendar.busy(from, to, tz),
+            _ => Vec::new(),
+        };
+
+        slots
+            .into_iter()
+            .zip(periods)
+            .map(|(slot, (start_time, end_time))| {
+                let is_busy = busy
+                    .iter()
+                    .any(|(busy_start, busy_end)| *busy_start < end_time && start_time < *busy_end);
+                (slot, is_busy)
+            })
+            .collect()
+    }
+
+    /// The start of every slot in the grid, with folded slots split back up.
+    pub fn slot_starts(&self) -> Vec<(DateTime<Utc>, Recurrence)> {
+        let mut starts = Vec::new();
+        for slot in &self.slots {
+            let mut start_time = slot.start_time;
+            while start_time < slot.end_time {
+                starts.push((start_time, slot.recurrence));
+                start_time += self.slot_duration;
+            }
+        }
+        starts
+    }
+
+    /// The start of the first slot and the end of the last one.
+    pub fn date_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let start = self.slots.iter().map(|slot| slot.start_time).min()?;
+        let end = self.slots.iter().map(|slot| slot.end_time).max()?;
+        Some((start, end))
+    }
+}
+
+impl EventMode {
+    /// when2meet stores the slots of a days of the week event as timestamps in a reference week
+    /// at the start of the Unix epoch, so anything before 1980 cannot be a real date.
+    pub fn detect(start_times: &[DateTime<Utc>]) -> Self {
+        let cutoff = Utc.with_ymd_and_hms(1980, 1, 1, 0, 0, 0).unwrap();
+
+        match start_times.iter().max() {
+            Some(latest) if *latest < cutoff => EventMode::DaysOfTheWeek,
+            _ => EventMode::SpecificDates,
+        }
+    }
+}
+
+/// The smallest gap between consecutive slot start times. Larger gaps are the breaks between the
+/// grid's columns, e.g. overnight or between non-consecutive days. Falls back to when2meet's
+/// 15 minutes when there are too few slots to tell.
+pub fn detect_slot_duration(start_times: &[DateTime<Utc>]) -> Duration {
+    let mut start_times = start_times.to_vec();
+    start_times.sort();
+
+    start_times
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|gap| *gap > Duration::zero())
+        .min()
+        .unwrap_or_else(|| Duration::minutes(15))
+}
+
+/// The index of the first slot that would end past the last time chrono can represent, given the
+/// slot duration `Event::new` detects for these start times.
+pub(crate) fn find_unrepresentable_slot(start_times: &[DateTime<Utc>]) -> Option<usize> {
+    let slot_duration = detect_slot_duration(start_times);
+
+    start_times
+        .iter()
+        .position(|start_time| start_time.checked_add_signed(slot_duration).is_none())
+}
+
 impl Slot {
-    pub fn new(start_time: DateTime<Utc>, people: Vec<Person>) -> Self {
+    pub fn new(start_time: DateTime<Utc>, slot_duration: Duration, people: Vec<Person>) -> Self {
         Self {
             start_time,
-            end_time: start_time + Duration::minutes(15),
+            end_time: start_time + slot_duration,
+            recurrence: Recurrence::Once,
             people,
         }
     }
+
+    pub fn weekday(&self) -> Weekday {
+        self.start_time.weekday()
+    }
+
+    /// The real time of the slot, or for a weekly slot of its next occurrence on or after
+    /// `week_of`, with the wall-clock time taken to be in `tz`.
+    fn calendar_period(&self, tz: Tz, week_of: DateTime<Utc>) -> Period {
+        if self.recurrence == Recurrence::Once {
+            return (self.start_time, self.end_time);
+        }
+
+        let week_of = week_of.with_timezone(&tz).date_naive();
+        let days_ahead = (7 + self.weekday().num_days_from_monday()
+            - week_of.weekday().num_days_from_monday())
+            % 7;
+        let local = (week_of + Duration::days(days_ahead.into())).and_time(self.start_time.time());
+        let start_time = tz
+            .from_local_datetime(&local)
+            .earliest()
+            .unwrap_or_else(|| tz.from_utc_datetime(&local))
+            .with_timezone(&Utc);
+
+        (start_time, start_time + (self.end_time - self.start_time))
+    }
 }
 
 pub fn fold(slots: Vec<Slot>) -> Vec<Slot> {
@@ -30,7 +544,10 @@ pub fn fold(slots: Vec<Slot>) -> Vec<Slot> {
         .into_iter()
         .fold(Vec::new(), |mut folded_slots, slot| {
             if let Some(last_slot) = folded_slots.last_mut() {
-                if last_slot.end_time == slot.start_time && last_slot.people == slot.people {
+                if last_slot.end_time == slot.start_time
+                    && last_slot.recurrence == slot.recurrence
+                    && last_slot.people == slot.people
+                {
                     last_slot.end_time = slot.end_time;
                     return folded_slots;
                 }
@@ -40,27 +557,53 @@ pub fn fold(slots: Vec<Slot>) -> Vec<Slot> {
         })
 }
 
+impl Slot {
+    /// The person's name, followed by their ID when someone else in the slot has the same name.
+    fn display_name(&self, person: &Person) -> String {
+        let shared = self
+            .people
+            .iter()
+            .any(|other| other.id != person.id && other.name == person.name);
+
+        if shared {
+            format!("{} ({})", person.name, person.id)
+        } else {
+            person.name.to_string()
+        }
+    }
+}
+
 impl std::fmt::Display for Slot {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let tz_str = match get_timezone() {
-            Ok(tz) => tz,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let formatted_timestamp = match self.recurrence {
+            Recurrence::Once => {
+                let tz_str = match get_timezone() {
+                    Ok(tz) => tz,
+                    Err(_) => return Err(std::fmt::Error),
+                };
 
-        let tz: Tz = match tz_str.parse() {
-            Ok(tz) => tz,
-            Err(_) => return Err(std::fmt::Error),
-        };
+                let tz: Tz = match tz_str.parse() {
+                    Ok(tz) => tz,
+                    Err(_) => return Err(std::fmt::Error),
+                };
 
-        let offset = tz.offset_from_utc_date(&Utc::now().date_naive());
-        let abbreviation = offset.abbreviation();
+                let offset = tz.offset_from_utc_date(&Utc::now().date_naive());
+                let abbreviation = offset.abbreviation();
 
-        let formatted_timestamp = format!(
-            "{} - {} {}",
-            self.start_time.format("%A %I:%M%P"),
-            self.end_time.format("%I:%M%P"),
-            abbreviation
-        );
+                format!(
+                    "{} - {} {}",
+                    self.start_time.format("%A %I:%M%P"),
+                    self.end_time.format("%I:%M%P"),
+                    abbreviation
+                )
+            }
+            Recurrence::Weekly => format!(
+                "{}s {} - {}",
+                self.start_time.format("%A"),
+                self.start_time.format("%I:%M%P"),
+                self.end_time.format("%I:%M%P")
+            ),
+        };
 
         writeln!(f, "Timestamp: {}", formatted_timestamp)?;
 
@@ -71,7 +614,7 @@ impl std::fmt::Display for Slot {
             writeln!(f, "Available People:")?;
 
             for person in available_people {
-                writeln!(f, "- {}", person.name)?;
+                writeln!(f, "- {}", self.display_name(person))?;
             }
         }
 
@@ -79,7 +622,7 @@ impl std::fmt::Display for Slot {
             writeln!(f, "Unavailable People:")?;
 
             for person in unavailable_people {
-                writeln!(f, "- {}", person.name)?;
+                writeln!(f, "- {}", self.display_name(person))?;
             }
         }
 
@@ -89,9 +632,14 @@ impl std::fmt::Display for Slot {
 
 #[cfg(test)]
 mod tests {
+    use crate::fetch_availability::aliases::Aliases;
     use crate::fetch_availability::fold;
-    use crate::fetch_availability::model::{Person, Slot};
-    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use crate::fetch_availability::ics::Calendar;
+    use crate::fetch_availability::model::{
+        detect_slot_duration, Event, EventMetadata, EventMode, Participant, Person, Recurrence,
+        Slot,
+    };
+    use chrono::{DateTime, Duration, TimeZone, Utc, Weekday};
     use chrono_tz::{OffsetName, Tz};
     use iana_time_zone::get_timezone;
 
@@ -102,16 +650,20 @@ mod tests {
                 DateTime::parse_from_str("1693746000", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -121,16 +673,20 @@ mod tests {
                 DateTime::parse_from_str("1693746900", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -140,16 +696,20 @@ mod tests {
                 DateTime::parse_from_str("1693747800", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -169,16 +729,20 @@ mod tests {
                     end_time: DateTime::parse_from_str("1693747800", "%s")
                         .unwrap()
                         .with_timezone(&Utc),
+                    recurrence: Recurrence::Once,
                     people: vec![
                         Person {
+                            id: "94102857".to_string().into_boxed_str(),
                             name: "Muneer".to_string().into_boxed_str(),
                             available: false,
                         },
                         Person {
+                            id: "94102860".to_string().into_boxed_str(),
                             name: "Brian".to_string().into_boxed_str(),
                             available: false,
                         },
                         Person {
+                            id: "94102911".to_string().into_boxed_str(),
                             name: "Garrett".to_string().into_boxed_str(),
                             available: false,
                         },
@@ -188,16 +752,20 @@ mod tests {
                     DateTime::parse_from_str("1693747800", "%s")
                         .unwrap()
                         .with_timezone(&Utc),
+                    Duration::minutes(15),
                     vec![
                         Person {
+                            id: "94102857".to_string().into_boxed_str(),
                             name: "Muneer".to_string().into_boxed_str(),
                             available: true,
                         },
                         Person {
+                            id: "94102860".to_string().into_boxed_str(),
                             name: "Brian".to_string().into_boxed_str(),
                             available: false,
                         },
                         Person {
+                            id: "94102911".to_string().into_boxed_str(),
                             name: "Garrett".to_string().into_boxed_str(),
                             available: false,
                         },
@@ -217,12 +785,15 @@ mod tests {
         let slot = Slot {
             start_time: Utc::now(),
             end_time: Utc::now() + Duration::hours(2),
+            recurrence: Recurrence::Once,
             people: vec![
                 Person {
+                    id: "94102857".to_string().into_boxed_str(),
                     name: "Muneer".to_string().into_boxed_str(),
                     available: true,
                 },
                 Person {
+                    id: "94102911".to_string().into_boxed_str(),
                     name: "Garrett".to_string().into_boxed_str(),
                     available: false,
                 },
@@ -247,4 +818,546 @@ mod tests {
 
         assert_eq!(expected_output, actual_output);
     }
+
+    #[test]
+    fn test_slot_display_duplicate_names() {
+        let slot = Slot::new(
+            DateTime::parse_from_str("1693746000", "%s")
+                .unwrap()
+                .with_timezone(&Utc),
+            Duration::minutes(15),
+            vec![
+                Person {
+                    id: "94102857".to_string().into_boxed_str(),
+                    name: "Alex".to_string().into_boxed_str(),
+                    available: true,
+                },
+                Person {
+                    id: "94102860".to_string().into_boxed_str(),
+                    name: "Alex".to_string().into_boxed_str(),
+                    available: false,
+                },
+                Person {
+                    id: "94102911".to_string().into_boxed_str(),
+                    name: "Garrett".to_string().into_boxed_str(),
+                    available: true,
+                },
+            ],
+        );
+
+        let actual_output = format!("{}", slot);
+
+        assert!(actual_output.ends_with(
+            "Available People:\n- Alex (94102857)\n- Garrett\nUnavailable People:\n- Alex (94102860)\n"
+        ));
+    }
+
+    fn timestamp(seconds: &str) -> DateTime<Utc> {
+        DateTime::parse_from_str(seconds, "%s")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_detect_slot_duration() {
+        let start_times = vec![
+            timestamp("1693746000"),
+            timestamp("1693746900"),
+            timestamp("1693747800"),
+        ];
+
+        assert_eq!(detect_slot_duration(&start_times), Duration::minutes(15));
+    }
+
+    #[test]
+    fn test_detect_slot_duration_irregular_grid() {
+        // 30 minute slots from 9:00am to 10:00am on two days, plus an unsorted stray slot.
+        let start_times = vec![
+            timestamp("1693818000"),
+            timestamp("1693731600"),
+            timestamp("1693733400"),
+            timestamp("1693819800"),
+            timestamp("1693904400"),
+        ];
+
+        assert_eq!(detect_slot_duration(&start_times), Duration::minutes(30));
+    }
+
+    #[test]
+    fn test_detect_slot_duration_single_slot() {
+        assert_eq!(
+            detect_slot_duration(&[timestamp("1693746000")]),
+            Duration::minutes(15)
+        );
+        assert_eq!(detect_slot_duration(&[]), Duration::minutes(15));
+    }
+
+    #[test]
+    fn test_event_new_does_not_fold_across_gaps() {
+        let people = || {
+            vec![Person {
+                id: "94102857".to_string().into_boxed_str(),
+                name: "Muneer".to_string().into_boxed_str(),
+                available: true,
+            }]
+        };
+
+        // 9:00am and 9:30am on one day, then 9:00am the next day.
+        let event = Event::new(vec![
+            (timestamp("1693731600"), people()),
+            (timestamp("1693733400"), people()),
+            (timestamp("1693818000"), people()),
+        ]);
+
+        assert_eq!(event.slot_duration, Duration::minutes(30));
+        assert_eq!(
+            event.slots,
+            vec![
+                Slot {
+                    start_time: timestamp("1693731600"),
+                    end_time: timestamp("1693735200"),
+                    recurrence: Recurrence::Once,
+                    people: people(),
+                },
+                Slot::new(timestamp("1693818000"), Duration::minutes(30), people()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_new_metadata() {
+        let people = |available| {
+            vec![
+                Person {
+                    id: "94102857".to_string().into_boxed_str(),
+                    name: "Muneer".to_string().into_boxed_str(),
+                    available,
+                },
+                Person {
+                    id: "94102860".to_string().into_boxed_str(),
+                    name: "Brian".to_string().into_boxed_str(),
+                    available: false,
+                },
+            ]
+        };
+
+        let event = Event::new(vec![
+            (timestamp("1693746000"), people(false)),
+            (timestamp("1693746900"), people(true)),
+        ])
+        .with_metadata(EventMetadata {
+            title: Some("Team Sync".to_string().into_boxed_str()),
+            timezone: Some("America/New_York".to_string().into_boxed_str()),
+        });
+
+        assert_eq!(event.title.as_deref(), Some("Team Sync"));
+        assert_eq!(event.timezone, Some(Tz::America__New_York));
+        assert_eq!(event.mode, EventMode::SpecificDates);
+        assert_eq!(
+            event.participants,
+            vec![
+                Participant {
+                    id: "94102857".to_string().into_boxed_str(),
+                    name: "Muneer".to_string().into_boxed_str(),
+                },
+                Participant {
+                    id: "94102860".to_string().into_boxed_str(),
+                    name: "Brian".to_string().into_boxed_str(),
+                },
+            ]
+        );
+        assert_eq!(
+            event.date_range(),
+            Some((timestamp("1693746000"), timestamp("1693747800")))
+        );
+    }
+
+    #[test]
+    fn test_event_with_metadata_unknown_timezone() {
+        let event = Event::new(vec![]).with_metadata(EventMetadata {
+            title: None,
+            timezone: Some("Mars/Olympus_Mons".to_string().into_boxed_str()),
+        });
+
+        assert_eq!(event.timezone, None);
+        assert_eq!(event.date_range(), None);
+    }
+
+    #[test]
+    fn test_event_mode_detect() {
+        assert_eq!(
+            EventMode::detect(&[timestamp("1693746000")]),
+            EventMode::SpecificDates
+        );
+        // Monday 9:00am of when2meet's reference week.
+        assert_eq!(
+            EventMode::detect(&[timestamp("378000")]),
+            EventMode::DaysOfTheWeek
+        );
+        assert_eq!(EventMode::detect(&[]), EventMode::SpecificDates);
+    }
+
+    #[test]
+    fn test_event_new_days_of_the_week() {
+        let people = || {
+            vec![Person {
+                id: "94102857".to_string().into_boxed_str(),
+                name: "Muneer".to_string().into_boxed_str(),
+                available: true,
+            }]
+        };
+
+        // Monday 2:00pm to 3:00pm of when2meet's reference week, in 30 minute slots.
+        let event = Event::new(vec![
+            (timestamp("396000"), people()),
+            (timestamp("397800"), people()),
+        ]);
+
+        assert_eq!(event.mode, EventMode::DaysOfTheWeek);
+        assert_eq!(event.slots.len(), 1);
+        assert_eq!(event.slots[0].recurrence, Recurrence::Weekly);
+        assert_eq!(event.slots[0].weekday(), Weekday::Mon);
+        assert_eq!(
+            format!("{}", event.slots[0]),
+            "Timestamp: Mondays 02:00pm - 03:00pm\nAvailable People:\n- Muneer\n"
+        );
+    }
+
+    fn person(id: &str, name: &str, available: bool) -> Person {
+        Person {
+            id: id.to_string().into_boxed_str(),
+            name: name.to_string().into_boxed_str(),
+            available,
+        }
+    }
+
+    #[test]
+    fn test_event_merge_concatenates_and_merges_people_by_name() {
+        let week_1 = Event::new(vec![
+            (
+                timestamp("1693746000"),
+                vec![person("1", "Muneer", true), person("2", "Brian", false)],
+            ),
+            (
+                timestamp("1693746900"),
+                vec![person("1", "Muneer", true), person("2", "Brian", true)],
+            ),
+        ])
+        .with_metadata(EventMetadata {
+            title: Some("Week 1".to_string().into_boxed_str()),
+            timezone: None,
+        });
+        // The same people get new IDs in another event, and Garrett only responded to this one.
+        let week_2 = Event::new(vec![(
+            timestamp("1694350800"),
+            vec![
+                person("11", "Brian", true),
+                person("12", "Muneer", true),
+                person("13", "Garrett", true),
+            ],
+        )])
+        .with_metadata(EventMetadata {
+            title: Some("Week 2".to_string().into_boxed_str()),
+            timezone: None,
+        });
+
+        let merged = Event::merge(vec![week_1, week_2]).unwrap();
+
+        assert_eq!(
+            merged.title,
+            Some("Week 1, Week 2".to_string().into_boxed_str())
+        );
+        assert_eq!(
+            merged.participants,
+            vec![
+                Participant {
+                    id: "0:1".to_string().into_boxed_str(),
+                    name: "Muneer".to_string().into_boxed_str(),
+                },
+                Participant {
+                    id: "0:2".to_string().into_boxed_str(),
+                    name: "Brian".to_string().into_boxed_str(),
+                },
+                Participant {
+                    id: "1:13".to_string().into_boxed_str(),
+                    name: "Garrett".to_string().into_boxed_str(),
+                },
+            ]
+        );
+        assert_eq!(
+            merged.slots,
+            vec![
+                Slot {
+                    start_time: timestamp("1693746000"),
+                    end_time: timestamp("1693746900"),
+                    recurrence: Recurrence::Once,
+                    people: vec![
+                        person("0:1", "Muneer", true),
+                        person("0:2", "Brian", false),
+                        person("1:13", "Garrett", false),
+                    ],
+                },
+                Slot {
+                    start_time: timestamp("1693746900"),
+                    end_time: timestamp("1693747800"),
+                    recurrence: Recurrence::Once,
+                    people: vec![
+                        person("0:1", "Muneer", true),
+                        person("0:2", "Brian", true),
+                        person("1:13", "Garrett", false),
+                    ],
+                },
+                Slot {
+                    start_time: timestamp("1694350800"),
+                    end_time: timestamp("1694351700"),
+                    recurrence: Recurrence::Once,
+                    people: vec![
+                        person("0:1", "Muneer", true),
+                        person("0:2", "Brian", true),
+                        person("1:13", "Garrett", true),
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_merge_scopes_reused_ids() {
+        // Both events gave ID 1 to a different person.
+        let first = Event::new(vec![(
+            timestamp("1693746000"),
+            vec![person("1", "Muneer", true)],
+        )]);
+        let second = Event::new(vec![(
+            timestamp("1693746900"),
+            vec![person("1", "Brian", true)],
+        )]);
+
+        let merged = Event::merge(vec![first, second]).unwrap();
+
+        assert_eq!(
+            merged
+                .participants
+                .iter()
+                .map(|participant| (&*participant.id, &*participant.name))
+                .collect::<Vec<_>>(),
+            vec![("0:1", "Muneer"), ("1:1", "Brian")]
+        );
+        assert_eq!(
+            merged.slots[0].people,
+            vec![person("0:1", "Muneer", true), person("1:1", "Brian", false)]
+        );
+        assert_eq!(
+            merged.slots[1].people,
+            vec![person("0:1", "Muneer", false), person("1:1", "Brian", true)]
+        );
+    }
+
+    #[test]
+    fn test_event_merge_keeps_shared_names_apart() {
+        // Two people named Brian responded to the second event, so neither is the first Brian.
+        let first = Event::new(vec![(
+            timestamp("1693746000"),
+            vec![person("1", "Brian", true), person("2", "Muneer", true)],
+        )]);
+        let second = Event::new(vec![(
+            timestamp("1693746900"),
+            vec![
+                person("11", "Brian", true),
+                person("12", "Brian", false),
+                person("13", "Muneer", true),
+            ],
+        )]);
+
+        let merged = Event::merge(vec![first, second]).unwrap();
+
+        assert_eq!(
+            merged
+                .participants
+                .iter()
+                .map(|participant| (&*participant.id, &*participant.name))
+                .collect::<Vec<_>>(),
+            vec![
+                ("0:1", "Brian"),
+                ("0:2", "Muneer"),
+                ("1:11", "Brian"),
+                ("1:12", "Brian"),
+            ]
+        );
+        assert_eq!(
+            merged.slots[1].people,
+            vec![
+                person("0:1", "Brian", false),
+                person("0:2", "Muneer", true),
+                person("1:11", "Brian", true),
+                person("1:12", "Brian", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_merge_aligns_overlapping_slots() {
+        // 30 minute slots in one event, 15 minute slots in the other, overlapping for 15 minutes.
+        let first = Event::new(vec![
+            (timestamp("1693746000"), vec![person("1", "Muneer", false)]),
+            (timestamp("1693747800"), vec![person("1", "Muneer", true)]),
+        ]);
+        let second = Event::new(vec![
+            (timestamp("1693746900"), vec![person("2", "Muneer", true)]),
+            (timestamp("1693747800"), vec![person("2", "Muneer", true)]),
+        ]);
+
+        let merged = Event::merge(vec![first, second]).unwrap();
+
+        assert_eq!(merged.slot_duration, Duration::minutes(15));
+        assert_eq!(merged.participants.len(), 1);
+        assert_eq!(
+            merged
+                .slots
+                .iter()
+                .map(|slot| (slot.start_time, slot.end_time, slot.people[0].available))
+                .collect::<Vec<_>>(),
+            vec![
+                (timestamp("1693746000"), timestamp("1693746900"), false),
+                (timestamp("1693746900"), timestamp("1693749600"), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_with_aliases() {
+        let aliases = Aliases::from_toml(r#"Garrett = ["garrett l", "GL"]"#).unwrap();
+        // Garrett responded twice, under different names.
+        let event = Event::new(vec![
+            (
+                timestamp("1693746000"),
+                vec![
+                    person("1", "Muneer", true),
+                    person("2", "garrett l", true),
+                    person("3", "GL", false),
+                ],
+            ),
+            (
+                timestamp("1693746900"),
+                vec![
+                    person("1", "Muneer", true),
+                    person("2", "garrett l", false),
+                    person("3", "GL", true),
+                ],
+            ),
+        ]);
+
+        let event = event.with_aliases(&aliases);
+
+        assert_eq!(
+            event.participants,
+            vec![
+                Participant {
+                    id: "1".to_string().into_boxed_str(),
+                    name: "Muneer".to_string().into_boxed_str(),
+                },
+                Participant {
+                    id: "2".to_string().into_boxed_str(),
+                    name: "Garrett".to_string().into_boxed_str(),
+                },
+            ]
+        );
+        assert_eq!(
+            event.slots,
+            vec![Slot {
+                start_time: timestamp("1693746000"),
+                end_time: timestamp("1693747800"),
+                recurrence: Recurrence::Once,
+                people: vec![person("1", "Muneer", true), person("2", "Garrett", true)],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_event_with_calendar() {
+        let calendar = Calendar::parse(
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:20230903T131500Z\nDTEND:20230903T134500Z\nEND:VEVENT\nEND:VCALENDAR\n",
+        )
+        .unwrap();
+        let event = Event::new(vec![
+            (timestamp("1693746000"), vec![person("1", "Muneer", true)]),
+            (timestamp("1693746900"), vec![person("1", "Muneer", true)]),
+            (timestamp("1693747800"), vec![person("1", "Muneer", true)]),
+            (timestamp("1693748700"), vec![person("1", "Muneer", true)]),
+        ]);
+
+        let event = event.with_calendar("Dana", &calendar, Utc::now());
+
+        assert_eq!(event.participants.len(), 2);
+        assert_eq!(
+            event
+                .slots
+                .iter()
+                .map(|slot| (slot.start_time, slot.people[1].available))
+                .collect::<Vec<_>>(),
+            vec![
+                (timestamp("1693746000"), true),
+                (timestamp("1693746900"), false),
+                (timestamp("1693748700"), true),
+            ]
+        );
+        assert_eq!(event.slots[1].end_time, timestamp("1693748700"));
+        assert_eq!(&*event.slots[0].people[1].id, "ics:Dana");
+    }
+
+    #[test]
+    fn test_event_with_calendar_days_of_the_week() {
+        // Mondays at 14:00 in when2meet's reference week, and a weekly Monday 14:00 meeting.
+        let calendar = Calendar::parse(
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART;TZID=Europe/London:20230904T140000\nDURATION:PT15M\nRRULE:FREQ=WEEKLY\nEND:VEVENT\nEND:VCALENDAR\n",
+        )
+        .unwrap();
+        let event = Event::new(vec![
+            (timestamp("396000"), vec![person("1", "Muneer", true)]),
+            (timestamp("396900"), vec![person("1", "Muneer", true)]),
+        ])
+        .with_metadata(EventMetadata {
+            title: None,
+            timezone: Some("Europe/London".to_string().into_boxed_str()),
+        });
+
+        let event = event.with_calendar("Dana", &calendar, timestamp("1700000000"));
+
+        assert_eq!(event.slots.len(), 2);
+        assert!(!event.slots[0].people[1].available);
+        assert!(event.slots[1].people[1].available);
+    }
+
+    #[test]
+    fn test_event_without_busy() {
+        let calendar = Calendar::parse(
+            "BEGIN:VCALENDAR\nBEGIN:VFREEBUSY\nFREEBUSY:20230903T131500Z/PT15M\nEND:VFREEBUSY\nEND:VCALENDAR\n",
+        )
+        .unwrap();
+        let event = Event::new(vec![
+            (timestamp("1693746000"), vec![person("1", "Muneer", true)]),
+            (timestamp("1693746900"), vec![person("1", "Muneer", true)]),
+            (timestamp("1693747800"), vec![person("1", "Muneer", true)]),
+        ]);
+
+        assert_eq!(
+            event.calendar_range(Utc::now()),
+            Some((timestamp("1693746000"), timestamp("1693748700")))
+        );
+
+        let event = event.without_busy(&calendar, Utc::now());
+
+        assert_eq!(event.participants.len(), 1);
+        assert_eq!(
+            event
+                .slots
+                .iter()
+                .map(|slot| (slot.start_time, slot.end_time))
+                .collect::<Vec<_>>(),
+            vec![
+                (timestamp("1693746000"), timestamp("1693746900")),
+                (timestamp("1693747800"), timestamp("1693748700")),
+            ]
+        );
+    }
 }
@@ -1,86 +1,297 @@
+use crate::fetch_availability::chrome::ChromeOptions;
 use crate::fetch_availability::errors::{
     FetchError, HeadlessChromeError, ParseError, ParseWhen2MeetError, ProcessResultError,
 };
-use crate::fetch_availability::model::{fold, Person, Slot};
+use crate::fetch_availability::model::{find_unrepresentable_slot, Event, EventMetadata, Person};
+use crate::fetch_availability::retry::{retry, Deadline};
+use crate::fetch_availability::snapshot::Snapshot;
 use chrono::{DateTime, Utc};
+use headless_chrome::protocol::cdp::Runtime::{self, RemoteObject};
 use headless_chrome::{Browser, Tab};
+use serde::Deserialize;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use url::Url;
 
-pub fn parse_when2meet(url: &Url) -> Result<Vec<Slot>, ParseWhen2MeetError> {
-    let browser = match Browser::default() {
+pub fn parse_when2meet(url: &Url, options: &ChromeOptions) -> Result<Event, ParseWhen2MeetError> {
+    let ((names, ids, avail_matrix), metadata) = fetch_when2meet(url, options)?;
+
+    let event = match process_names_and_matrix(names, ids, avail_matrix) {
+        Ok(event) => event,
+        Err(process_result_error) => {
+            return Err(ParseWhen2MeetError::ProcessResult(process_result_error))
+        }
+    };
+
+    Ok(event.with_metadata(metadata))
+}
+
+pub fn snapshot_when2meet(
+    url: &Url,
+    options: &ChromeOptions,
+) -> Result<Snapshot, ParseWhen2MeetError> {
+    let ((names, ids, avail_matrix), metadata) = fetch_when2meet(url, options)?;
+
+    let mut snapshot = match Snapshot::from_raw(names, ids, avail_matrix) {
+        Ok(snapshot) => snapshot,
+        Err(process_result_error) => {
+            return Err(ParseWhen2MeetError::ProcessResult(process_result_error))
+        }
+    };
+
+    snapshot.url = Some(url.clone());
+    snapshot.metadata = metadata;
+
+    Ok(snapshot)
+}
+
+type NamesIdsAndMatrix = (Vec<Box<str>>, Vec<i64>, Vec<Box<str>>);
+
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The lengths of the availability globals at one point during page load.
+#[derive(Debug, PartialEq, Deserialize)]
+struct Readiness {
+    loaded: bool,
+    defined: bool,
+    names: usize,
+    ids: usize,
+    slots: usize,
+    available_at_slots: usize,
+}
+
+impl Readiness {
+    fn is_ready(&self) -> bool {
+        self.defined
+            && self.names == self.ids
+            && self.slots > 0
+            && self.slots == self.available_at_slots
+    }
+}
+
+impl std::fmt::Display for Readiness {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if !self.defined {
+            return write!(f, "availability globals are not defined");
+        }
+
+        write!(
+            f,
+            "{} names for {} IDs, {} slots for {} availability rows",
+            self.names, self.ids, self.slots, self.available_at_slots
+        )
+    }
+}
+
+/// Loads the event and reads everything off the page, starting over with a fresh browser when an
+/// attempt fails for a transient reason.
+fn fetch_when2meet(
+    url: &Url,
+    options: &ChromeOptions,
+) -> Result<(NamesIdsAndMatrix, EventMetadata), ParseWhen2MeetError> {
+    retry(&options.retry_policy(), url.as_str(), |deadline| {
+        let (_browser, tab) = open_when2meet(url, options, deadline)?;
+
+        let names_ids_and_matrix = fetch_names_ids_and_matrix(&tab, url, deadline)?;
+
+        let metadata = fetch_and_parse_metadata(&tab, url, deadline)?;
+
+        Ok((names_ids_and_matrix, metadata))
+    })
+}
+
+/// Reads the event off a tab opened with `open_when2meet`.
+pub(crate) fn read_when2meet(
+    tab: &Arc<Tab>,
+    url: &Url,
+    deadline: &Deadline,
+) -> Result<Event, ParseWhen2MeetError> {
+    let (names, ids, avail_matrix) = fetch_names_ids_and_matrix(tab, url, deadline)?;
+
+    let metadata = fetch_and_parse_metadata(tab, url, deadline)?;
+
+    match process_names_and_matrix(names, ids, avail_matrix) {
+        Ok(event) => Ok(event.with_metadata(metadata)),
+        Err(process_result_error) => Err(ParseWhen2MeetError::ProcessResult(process_result_error)),
+    }
+}
+
+fn fetch_names_ids_and_matrix(
+    tab: &Arc<Tab>,
+    url: &Url,
+    deadline: &Deadline,
+) -> Result<NamesIdsAndMatrix, ParseWhen2MeetError> {
+    let raw_names = match fetch_people_names(tab, url, deadline) {
+        Ok(raw_names) => raw_names,
+        Err(fetch_error) => return Err(ParseWhen2MeetError::Fetch(fetch_error)),
+    };
+
+    let names = match parse_people_names_from_result(raw_names) {
+        Ok(names) => names,
+        Err(parse_error) => return Err(ParseWhen2MeetError::Parse(parse_error)),
+    };
+
+    let raw_ids = match fetch_people_ids(tab, url, deadline) {
+        Ok(raw_ids) => raw_ids,
+        Err(fetch_error) => return Err(ParseWhen2MeetError::Fetch(fetch_error)),
+    };
+
+    let ids = match parse_people_ids_from_result(raw_ids) {
+        Ok(ids) => ids,
+        Err(parse_error) => return Err(ParseWhen2MeetError::Parse(parse_error)),
+    };
+
+    let raw_avail_matrix = match fetch_avail_matrix(tab, url, deadline) {
+        Ok(raw_avail_matrix) => raw_avail_matrix,
+        Err(fetch_error) => return Err(ParseWhen2MeetError::Fetch(fetch_error)),
+    };
+
+    let avail_matrix = match parse_avail_matrix_from_result(raw_avail_matrix) {
+        Ok(avail_matrix) => avail_matrix,
+        Err(parse_error) => return Err(ParseWhen2MeetError::Parse(parse_error)),
+    };
+
+    Ok((names, ids, avail_matrix))
+}
+
+fn fetch_and_parse_metadata(
+    tab: &Arc<Tab>,
+    url: &Url,
+    deadline: &Deadline,
+) -> Result<EventMetadata, ParseWhen2MeetError> {
+    let raw_metadata = match fetch_metadata(tab, url, deadline) {
+        Ok(raw_metadata) => raw_metadata,
+        Err(fetch_error) => return Err(ParseWhen2MeetError::Fetch(fetch_error)),
+    };
+
+    match parse_metadata_from_result(raw_metadata) {
+        Ok(metadata) => Ok(metadata),
+        Err(parse_error) => Err(ParseWhen2MeetError::Parse(parse_error)),
+    }
+}
+
+/// Launches headless Chrome and loads the event page. The browser must outlive the tab.
+pub(crate) fn open_when2meet(
+    url: &Url,
+    options: &ChromeOptions,
+    deadline: &Deadline,
+) -> Result<(Browser, Arc<Tab>), ParseWhen2MeetError> {
+    let mut launch_options = options.launch_options();
+    // Every call to Chrome waits at most this long for its response.
+    launch_options.idle_browser_timeout = launch_options
+        .idle_browser_timeout
+        .min(deadline.remaining());
+
+    let browser = match Browser::new(launch_options) {
         Ok(browser) => browser,
-        Err(_) => {
+        Err(source) => {
             return Err(ParseWhen2MeetError::HeadlessChrome(
-                HeadlessChromeError::FailedToLaunch,
+                HeadlessChromeError::FailedToLaunch(source),
             ))
         }
     };
 
     let tab = match browser.new_tab() {
         Ok(tab) => tab,
-        Err(_) => {
+        Err(source) => {
             return Err(ParseWhen2MeetError::HeadlessChrome(
-                HeadlessChromeError::FailedToNewTab,
+                HeadlessChromeError::FailedToNewTab(source),
             ))
         }
     };
 
+    tab.set_default_timeout(deadline.step_timeout());
+
     match tab.navigate_to(url.as_str()) {
         Ok(_) => {}
-        Err(_) => {
+        Err(source) => {
             return Err(ParseWhen2MeetError::HeadlessChrome(
-                HeadlessChromeError::FailedToNavigate,
+                HeadlessChromeError::FailedToNavigate {
+                    url: url.clone(),
+                    source,
+                },
             ))
         }
     }
 
+    tab.set_default_timeout(deadline.step_timeout());
+
     match tab.wait_until_navigated() {
         Ok(_) => {}
-        Err(_) => {
+        Err(source) => {
             return Err(ParseWhen2MeetError::HeadlessChrome(
-                HeadlessChromeError::FailedToWaitUntilNavigated,
+                HeadlessChromeError::FailedToWaitUntilNavigated {
+                    url: url.clone(),
+                    source,
+                },
             ))
         }
     }
 
-    let raw_names = match fetch_people_names(&tab) {
-        Ok(raw_names) => raw_names,
-        Err(fetch_error) => return Err(ParseWhen2MeetError::Fetch(fetch_error)),
-    };
+    wait_until_ready(&tab, url, deadline)?;
 
-    let names = match parse_people_names_from_result(raw_names) {
-        Ok(names) => names,
-        Err(parse_error) => return Err(ParseWhen2MeetError::Parse(parse_error)),
-    };
+    Ok((browser, tab))
+}
 
-    let raw_avail_matrix = match fetch_avail_matrix(&tab) {
-        Ok(raw_avail_matrix) => raw_avail_matrix,
-        Err(fetch_error) => return Err(ParseWhen2MeetError::Fetch(fetch_error)),
-    };
+/// Polls the page until the availability globals are populated, since they can lag behind
+/// navigation on slow pages and reading them early gives empty or partial data.
+fn wait_until_ready(
+    tab: &Arc<Tab>,
+    url: &Url,
+    deadline: &Deadline,
+) -> Result<(), ParseWhen2MeetError> {
+    let timeout = deadline.step_timeout();
+    let start = Instant::now();
+
+    loop {
+        let raw_readiness = match fetch_readiness(tab, url, deadline) {
+            Ok(raw_readiness) => raw_readiness,
+            Err(fetch_error) => return Err(ParseWhen2MeetError::Fetch(fetch_error)),
+        };
 
-    let avail_matrix = match parse_avail_matrix_from_result(raw_avail_matrix) {
-        Ok(avail_matrix) => avail_matrix,
-        Err(parse_error) => return Err(ParseWhen2MeetError::Parse(parse_error)),
-    };
+        let readiness = match parse_readiness_from_result(raw_readiness) {
+            Ok(readiness) => readiness,
+            Err(parse_error) => return Err(ParseWhen2MeetError::Parse(parse_error)),
+        };
 
-    let slots = match process_names_and_matrix(names, avail_matrix) {
-        Ok(slots) => slots,
-        Err(process_result_error) => {
-            return Err(ParseWhen2MeetError::ProcessResult(process_result_error))
+        if readiness.is_ready() {
+            return Ok(());
+        }
+
+        // Deleted events and other non-event pages load fine but never define the globals.
+        if readiness.loaded && !readiness.defined {
+            return Err(ParseWhen2MeetError::NotAnEvent {
+                location: url.to_string().into_boxed_str(),
+            });
+        }
+
+        if start.elapsed() >= timeout {
+            return Err(ParseWhen2MeetError::Fetch(FetchError::NotReady {
+                url: url.clone(),
+                waited: timeout,
+                state: readiness.to_string().into_boxed_str(),
+            }));
         }
-    };
 
-    Ok(slots)
+        std::thread::sleep(READINESS_POLL_INTERVAL);
+    }
 }
 
-fn process_names_and_matrix(
+pub(crate) fn process_names_and_matrix(
     names: Vec<Box<str>>,
+    ids: Vec<i64>,
     avail_matrix: Vec<Box<str>>,
-) -> Result<Vec<Slot>, ProcessResultError> {
-    let mut slots = Vec::new();
+) -> Result<Event, ProcessResultError> {
+    if names.len() != ids.len() {
+        return Err(ProcessResultError::NamesIdsMismatch {
+            names: names.len(),
+            ids: ids.len(),
+        });
+    }
 
-    for section in avail_matrix {
+    let mut rows: Vec<(DateTime<Utc>, Vec<Person>)> = Vec::new();
+
+    for (row, section) in avail_matrix.into_iter().enumerate() {
         let mut parts = section.split(',');
 
         let start_timestamp_str = match parts.next() {
@@ -97,71 +308,241 @@ fn process_names_and_matrix(
             }
         };
 
+        if let Some((previous_timestamp, _)) = rows.last() {
+            if start_timestamp <= *previous_timestamp {
+                return Err(ProcessResultError::AvailMatrixTimestampNotIncreasing {
+                    row,
+                    timestamp: start_timestamp_str.to_string(),
+                });
+            }
+        }
+
+        let parts: Vec<&str> = parts.collect();
+
+        if parts.len() != names.len() {
+            return Err(ProcessResultError::AvailMatrixRowLength {
+                row,
+                expected: names.len(),
+                found: parts.len(),
+            });
+        }
+
         let people = names
             .iter()
+            .zip(&ids)
             .zip(parts)
-            .map(|(name, available)| Person {
+            .map(|((name, id), available)| Person {
+                id: id.to_string().into_boxed_str(),
                 name: name.to_string().into_boxed_str(),
                 available: available == "1",
             })
             .collect();
 
-        slots.push(Slot::new(start_timestamp, people));
+        rows.push((start_timestamp, people));
+    }
+
+    let start_times: Vec<DateTime<Utc>> = rows.iter().map(|(start_time, _)| *start_time).collect();
+
+    if let Some(row) = find_unrepresentable_slot(&start_times) {
+        return Err(ProcessResultError::SlotOutOfRange {
+            row,
+            timestamp: start_times[row].timestamp().to_string(),
+        });
+    }
+
+    Ok(Event::new(rows))
+}
+
+/// Evaluates `js_func` in the tab, turning a thrown exception into an error that carries its text.
+/// Chrome stops the evaluation once the step timeout of `deadline` runs out.
+pub(crate) fn evaluate(
+    tab: &Arc<Tab>,
+    url: &Url,
+    js_func: &str,
+    deadline: &Deadline,
+) -> Result<RemoteObject, FetchError> {
+    // Deserialized rather than built field by field, since every headless_chrome release adds
+    // optional parameters to the struct and Chrome's defaults are what we want for the rest.
+    let method: Runtime::Evaluate = match serde_json::from_value(serde_json::json!({
+        "expression": js_func,
+        "timeout": deadline.step_timeout().as_millis() as f64,
+    })) {
+        Ok(method) => method,
+        Err(source) => {
+            return Err(FetchError::FailedEval {
+                url: url.clone(),
+                source: source.into(),
+            })
+        }
+    };
+
+    let evaluated = match tab.call_method(method) {
+        Ok(evaluated) => evaluated,
+        Err(source) => {
+            return Err(FetchError::FailedEval {
+                url: url.clone(),
+                source,
+            })
+        }
+    };
+
+    match evaluated.exception_details {
+        Some(details) => Err(FetchError::JsException {
+            url: url.clone(),
+            exception: details
+                .exception
+                .and_then(|exception| exception.description)
+                .unwrap_or(details.text),
+        }),
+        None => Ok(evaluated.result),
     }
+}
 
-    Ok(fold(slots))
+fn fetch_readiness(tab: &Arc<Tab>, url: &Url, deadline: &Deadline) -> Result<String, FetchError> {
+    let js_func = r#"
+    (function () {
+        const defined = typeof PeopleNames !== "undefined"
+            && typeof PeopleIDs !== "undefined"
+            && typeof TimeOfSlot !== "undefined"
+            && typeof AvailableAtSlot !== "undefined";
+        return JSON.stringify({
+            loaded: document.readyState === "complete",
+            defined: defined,
+            names: defined ? PeopleNames.length : 0,
+            ids: defined ? PeopleIDs.length : 0,
+            slots: defined ? TimeOfSlot.length : 0,
+            available_at_slots: defined ? AvailableAtSlot.length : 0,
+        });
+    })();
+    "#;
+
+    let readiness = evaluate(tab, url, js_func, deadline)?;
+
+    match readiness.value.as_ref().and_then(|value| value.as_str()) {
+        Some(value) => Ok(value.to_string()),
+        None => Err(FetchError::EvalNoValue { url: url.clone() }),
+    }
 }
 
-fn fetch_people_names(tab: &Arc<Tab>) -> Result<String, FetchError> {
+fn parse_readiness_from_result(raw_readiness: String) -> Result<Readiness, ParseError> {
+    match serde_json::from_str(&raw_readiness) {
+        Ok(readiness) => Ok(readiness),
+        Err(serde_error) => Err(ParseError::FailedReadinessParse(serde_error)),
+    }
+}
+
+fn fetch_metadata(tab: &Arc<Tab>, url: &Url, deadline: &Deadline) -> Result<String, FetchError> {
     let js_func = r#"
     (function () {
-        return PeopleNames.join(",")
+        const title = document.getElementById("NewEventNameDiv");
+        const timezone = document.getElementById("ParticipantTimeZone");
+        return JSON.stringify({
+            title: title ? title.innerText.trim() : null,
+            timezone: timezone ? timezone.value : null,
+        });
     })();
     "#;
 
-    let names = match tab.evaluate(js_func, false) {
-        Ok(result) => result,
-        Err(_) => return Err(FetchError::FailedEval),
-    };
+    let metadata = evaluate(tab, url, js_func, deadline)?;
+
+    match metadata.value.as_ref().and_then(|value| value.as_str()) {
+        Some(value) => Ok(value.to_string()),
+        None => Err(FetchError::EvalNoValue { url: url.clone() }),
+    }
+}
+
+fn parse_metadata_from_result(raw_metadata: String) -> Result<EventMetadata, ParseError> {
+    match serde_json::from_str(&raw_metadata) {
+        Ok(metadata) => Ok(metadata),
+        Err(serde_error) => Err(ParseError::FailedMetadataParse(serde_error)),
+    }
+}
+
+/// Returns the names as a JSON array so that commas, quotes and unicode in them survive intact.
+fn fetch_people_names(
+    tab: &Arc<Tab>,
+    url: &Url,
+    deadline: &Deadline,
+) -> Result<String, FetchError> {
+    let js_func = r#"
+    (function () {
+        return JSON.stringify(PeopleNames);
+    })();
+    "#;
+
+    let names = evaluate(tab, url, js_func, deadline)?;
+
+    match names.value.as_ref().and_then(|value| value.as_str()) {
+        Some(value) => Ok(value.to_string()),
+        None => Err(FetchError::EvalNoValue { url: url.clone() }),
+    }
+}
+
+fn parse_people_names_from_result(raw_names: String) -> Result<Vec<Box<str>>, ParseError> {
+    if raw_names.is_empty() {
+        return Err(ParseError::EmptyRaw);
+    }
 
-    let raw_names = match names.value {
+    match serde_json::from_str(&raw_names) {
+        Ok(names) => Ok(names),
+        Err(serde_error) => Err(ParseError::FailedNamesParse(serde_error)),
+    }
+}
+
+fn fetch_people_ids(tab: &Arc<Tab>, url: &Url, deadline: &Deadline) -> Result<String, FetchError> {
+    let js_func = r#"
+    (function () {
+        return PeopleIDs.join(",")
+    })();
+    "#;
+
+    let ids = evaluate(tab, url, js_func, deadline)?;
+
+    let raw_ids = match ids.value {
         Some(value) => value.to_string(),
-        None => return Err(FetchError::EvalNoValue),
+        None => return Err(FetchError::EvalNoValue { url: url.clone() }),
     };
 
-    Ok(raw_names)
+    Ok(raw_ids)
 }
 
-fn parse_people_names_from_result(raw_names: String) -> Result<Vec<Box<str>>, ParseError> {
-    if raw_names.len() <= 2 {
+fn parse_people_ids_from_result(raw_ids: String) -> Result<Vec<i64>, ParseError> {
+    if raw_ids.len() < 2 {
         return Err(ParseError::EmptyRaw);
     }
 
-    let names = &raw_names[1..raw_names.len() - 1];
+    // Nobody has responded yet.
+    if raw_ids.len() == 2 {
+        return Ok(Vec::new());
+    }
 
-    Ok(names
+    raw_ids[1..raw_ids.len() - 1]
         .split(',')
-        .map(|x| x.to_string().into_boxed_str())
-        .collect())
+        .map(|id| match id.parse() {
+            Ok(id) => Ok(id),
+            Err(_) => Err(ParseError::FailedIdParse { id: id.to_string() }),
+        })
+        .collect()
 }
 
-fn fetch_avail_matrix(tab: &Arc<Tab>) -> Result<String, FetchError> {
+fn fetch_avail_matrix(
+    tab: &Arc<Tab>,
+    url: &Url,
+    deadline: &Deadline,
+) -> Result<String, FetchError> {
     let js_func = r#"
     (function () {
         return AvailableAtSlot.map((slotData, i) => {
-            return `${TimeOfSlot[i]},${PeopleIDs.map(id => slotData.includes(id) ? 1 : 0).join(",")}`;
+            return [TimeOfSlot[i], ...PeopleIDs.map(id => slotData.includes(id) ? 1 : 0)].join(",");
         }).join("|");
     })();
     "#;
 
-    let avail_matrix = match tab.evaluate(js_func, false) {
-        Ok(result) => result,
-        Err(_) => return Err(FetchError::FailedEval),
-    };
+    let avail_matrix = evaluate(tab, url, js_func, deadline)?;
 
     let raw_avail_matrix = match avail_matrix.value {
         Some(value) => value.to_string(),
-        None => return Err(FetchError::EvalNoValue),
+        None => return Err(FetchError::EvalNoValue { url: url.clone() }),
     };
 
     Ok(raw_avail_matrix)
@@ -183,11 +564,13 @@ fn parse_avail_matrix_from_result(raw_avail_matrix: String) -> Result<Vec<Box<st
 #[cfg(test)]
 mod tests {
     use crate::fetch_availability::errors::{ParseError, ProcessResultError};
-    use crate::fetch_availability::model::{Person, Slot};
+    use crate::fetch_availability::model::{EventMetadata, Person, Slot};
     use crate::fetch_availability::parse::{
-        parse_avail_matrix_from_result, parse_people_names_from_result, process_names_and_matrix,
+        parse_avail_matrix_from_result, parse_metadata_from_result, parse_people_ids_from_result,
+        parse_people_names_from_result, parse_readiness_from_result, process_names_and_matrix,
+        Readiness,
     };
-    use chrono::{DateTime, Utc};
+    use chrono::{DateTime, Duration, Utc};
     use claims::{assert_err, assert_ok};
 
     #[test]
@@ -221,7 +604,7 @@ mod tests {
 
     #[test]
     fn test_parse_people_names_from_result_valid_str() {
-        let raw_names = "'Muneer,Brian,Garrett'".to_string();
+        let raw_names = r#"["Muneer","Brian","Garrett"]"#.to_string();
 
         let names = parse_people_names_from_result(raw_names);
 
@@ -235,9 +618,20 @@ mod tests {
         assert!(names[2] == "Garrett".to_string().into_boxed_str());
     }
 
+    #[test]
+    fn test_parse_people_names_from_result_no_respondents() {
+        let raw_names = "[]".to_string();
+
+        let names = parse_people_names_from_result(raw_names);
+
+        assert_ok!(&names);
+
+        assert!(names.unwrap().is_empty());
+    }
+
     #[test]
     fn test_parse_people_names_from_result_invalid_str() {
-        let raw_names = "''".to_string();
+        let raw_names = "".to_string();
 
         let names = parse_people_names_from_result(raw_names);
 
@@ -248,6 +642,118 @@ mod tests {
         assert!(matches!(names, ParseError::EmptyRaw));
     }
 
+    #[test]
+    fn test_parse_people_names_from_result_punctuation_and_unicode() {
+        let raw_names = r#"["Smith, Jane","O'Brien","Zoë \"Z\" 🦀","Garrett"]"#.to_string();
+
+        let names = parse_people_names_from_result(raw_names);
+
+        assert_ok!(&names);
+
+        assert_eq!(
+            names.unwrap(),
+            vec![
+                "Smith, Jane".to_string().into_boxed_str(),
+                "O'Brien".to_string().into_boxed_str(),
+                "Zoë \"Z\" 🦀".to_string().into_boxed_str(),
+                "Garrett".to_string().into_boxed_str(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_people_names_from_result_not_json() {
+        let raw_names = "'Muneer,Brian,Garrett'".to_string();
+
+        let names = parse_people_names_from_result(raw_names);
+
+        assert_err!(&names);
+
+        assert!(matches!(
+            names.unwrap_err(),
+            ParseError::FailedNamesParse(_)
+        ));
+    }
+
+    #[test]
+    fn test_process_names_and_matrix_name_with_comma_keeps_columns_aligned() {
+        let names =
+            parse_people_names_from_result(r#"["Smith, Jane","Brian"]"#.to_string()).unwrap();
+
+        let avail_matrix = vec!["1693746000,0,1".to_string().into_boxed_str()];
+
+        let event = process_names_and_matrix(names, vec![1, 2], avail_matrix).unwrap();
+
+        let people = &event.slots[0].people;
+
+        assert_eq!(&*people[0].name, "Smith, Jane");
+        assert!(!people[0].available);
+        assert_eq!(&*people[1].name, "Brian");
+        assert!(people[1].available);
+    }
+
+    #[test]
+    fn test_parse_people_ids_from_result_valid_str() {
+        let raw_ids = "'94102857,94102860,94102911'".to_string();
+
+        let ids = parse_people_ids_from_result(raw_ids);
+
+        assert_ok!(&ids);
+
+        assert_eq!(ids.unwrap(), vec![94102857, 94102860, 94102911]);
+    }
+
+    #[test]
+    fn test_parse_people_ids_from_result_invalid_id() {
+        let raw_ids = "'94102857,Brian'".to_string();
+
+        let ids = parse_people_ids_from_result(raw_ids);
+
+        assert_err!(&ids);
+
+        assert!(matches!(
+            ids.unwrap_err(),
+            ParseError::FailedIdParse { id } if id == "Brian"
+        ));
+    }
+
+    #[test]
+    fn test_parse_metadata_from_result_valid_str() {
+        let raw_metadata = r#"{"title":"Team Sync","timezone":"America/New_York"}"#.to_string();
+
+        let metadata = parse_metadata_from_result(raw_metadata);
+
+        assert_ok!(&metadata);
+
+        let metadata = metadata.unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Team Sync"));
+        assert_eq!(metadata.timezone.as_deref(), Some("America/New_York"));
+    }
+
+    #[test]
+    fn test_parse_metadata_from_result_missing_elements() {
+        let raw_metadata = r#"{"title":null,"timezone":null}"#.to_string();
+
+        let metadata = parse_metadata_from_result(raw_metadata);
+
+        assert_ok!(&metadata);
+
+        assert_eq!(metadata.unwrap(), EventMetadata::default());
+    }
+
+    #[test]
+    fn test_parse_metadata_from_result_invalid_str() {
+        let metadata = parse_metadata_from_result("undefined".to_string());
+
+        assert_err!(&metadata);
+
+        assert!(matches!(
+            metadata.unwrap_err(),
+            ParseError::FailedMetadataParse(_)
+        ));
+    }
+
     #[test]
     fn test_process_names_and_matrix_valid() {
         let names = vec![
@@ -262,11 +768,13 @@ mod tests {
             "1693747800,0,1,0".to_string().into_boxed_str(),
         ];
 
-        let slots = process_names_and_matrix(names, avail_matrix);
+        let ids = vec![94102857, 94102860, 94102911];
+
+        let slots = process_names_and_matrix(names, ids, avail_matrix);
 
         assert_ok!(&slots);
 
-        let slots = slots.unwrap();
+        let slots = slots.unwrap().slots;
 
         assert!(slots.len() == 3);
         assert!(
@@ -275,16 +783,20 @@ mod tests {
                     DateTime::parse_from_str("1693746000", "%s")
                         .unwrap()
                         .with_timezone(&Utc),
+                    Duration::minutes(15),
                     vec![
                         Person {
+                            id: "94102857".to_string().into_boxed_str(),
                             name: "Muneer".to_string().into_boxed_str(),
                             available: false
                         },
                         Person {
+                            id: "94102860".to_string().into_boxed_str(),
                             name: "Brian".to_string().into_boxed_str(),
                             available: false
                         },
                         Person {
+                            id: "94102911".to_string().into_boxed_str(),
                             name: "Garrett".to_string().into_boxed_str(),
                             available: false
                         }
@@ -297,16 +809,20 @@ mod tests {
                     DateTime::parse_from_str("1693746900", "%s")
                         .unwrap()
                         .with_timezone(&Utc),
+                    Duration::minutes(15),
                     vec![
                         Person {
+                            id: "94102857".to_string().into_boxed_str(),
                             name: "Muneer".to_string().into_boxed_str(),
                             available: true
                         },
                         Person {
+                            id: "94102860".to_string().into_boxed_str(),
                             name: "Brian".to_string().into_boxed_str(),
                             available: false
                         },
                         Person {
+                            id: "94102911".to_string().into_boxed_str(),
                             name: "Garrett".to_string().into_boxed_str(),
                             available: false
                         }
@@ -319,16 +835,20 @@ mod tests {
                     DateTime::parse_from_str("1693747800", "%s")
                         .unwrap()
                         .with_timezone(&Utc),
+                    Duration::minutes(15),
                     vec![
                         Person {
+                            id: "94102857".to_string().into_boxed_str(),
                             name: "Muneer".to_string().into_boxed_str(),
                             available: false
                         },
                         Person {
+                            id: "94102860".to_string().into_boxed_str(),
                             name: "Brian".to_string().into_boxed_str(),
                             available: true
                         },
                         Person {
+                            id: "94102911".to_string().into_boxed_str(),
                             name: "Garrett".to_string().into_boxed_str(),
                             available: false
                         }
@@ -347,7 +867,9 @@ mod tests {
 
         let avail_matrix = vec!["".to_string().into_boxed_str()];
 
-        let slots = process_names_and_matrix(names, avail_matrix);
+        let ids = vec![94102857, 94102860, 94102911];
+
+        let slots = process_names_and_matrix(names, ids, avail_matrix);
 
         assert_err!(&slots);
 
@@ -360,4 +882,148 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_process_names_and_matrix_no_respondents() {
+        let avail_matrix = vec![
+            "1693746000".to_string().into_boxed_str(),
+            "1693746900".to_string().into_boxed_str(),
+        ];
+
+        let event = process_names_and_matrix(Vec::new(), Vec::new(), avail_matrix);
+
+        assert_ok!(&event);
+
+        let event = event.unwrap();
+
+        assert!(event.participants.is_empty());
+        assert_eq!(event.slots.len(), 1);
+        assert!(event.slots[0].people.is_empty());
+    }
+
+    fn readiness(names: usize, ids: usize, slots: usize, available_at_slots: usize) -> Readiness {
+        Readiness {
+            loaded: true,
+            defined: true,
+            names,
+            ids,
+            slots,
+            available_at_slots,
+        }
+    }
+
+    #[test]
+    fn test_parse_readiness_from_result_valid_str() {
+        let raw_readiness =
+            r#"{"loaded":true,"defined":true,"names":3,"ids":3,"slots":4,"available_at_slots":4}"#
+                .to_string();
+
+        let parsed = parse_readiness_from_result(raw_readiness);
+
+        assert_ok!(&parsed);
+
+        assert_eq!(parsed.unwrap(), readiness(3, 3, 4, 4));
+    }
+
+    #[test]
+    fn test_parse_readiness_from_result_invalid_str() {
+        let parsed = parse_readiness_from_result("'3,3,4,4'".to_string());
+
+        assert_err!(&parsed);
+
+        assert!(matches!(
+            parsed.unwrap_err(),
+            ParseError::FailedReadinessParse(_)
+        ));
+    }
+
+    #[test]
+    fn test_readiness_is_ready() {
+        assert!(readiness(3, 3, 4, 4).is_ready());
+        assert!(readiness(0, 0, 4, 4).is_ready());
+        assert!(!readiness(3, 2, 4, 4).is_ready());
+        assert!(!readiness(3, 3, 4, 2).is_ready());
+        assert!(!readiness(3, 3, 0, 0).is_ready());
+        assert_eq!(
+            readiness(3, 2, 4, 4).to_string(),
+            "3 names for 2 IDs, 4 slots for 4 availability rows"
+        );
+        assert!(!Readiness {
+            loaded: false,
+            defined: false,
+            names: 0,
+            ids: 0,
+            slots: 0,
+            available_at_slots: 0,
+        }
+        .is_ready());
+    }
+
+    #[test]
+    fn test_process_names_and_matrix_names_ids_mismatch() {
+        let names = vec![
+            "Muneer".to_string().into_boxed_str(),
+            "Brian".to_string().into_boxed_str(),
+        ];
+
+        let avail_matrix = vec!["1693746000,0,1".to_string().into_boxed_str()];
+
+        let event = process_names_and_matrix(names, vec![94102857], avail_matrix);
+
+        assert_err!(&event);
+
+        assert_eq!(
+            event.unwrap_err(),
+            ProcessResultError::NamesIdsMismatch { names: 2, ids: 1 }
+        );
+    }
+
+    #[test]
+    fn test_process_names_and_matrix_row_length() {
+        let names = vec![
+            "Muneer".to_string().into_boxed_str(),
+            "Brian".to_string().into_boxed_str(),
+        ];
+
+        let avail_matrix = vec![
+            "1693746000,0,1".to_string().into_boxed_str(),
+            "1693746900,0,1,1".to_string().into_boxed_str(),
+        ];
+
+        let event = process_names_and_matrix(names, vec![94102857, 94102860], avail_matrix);
+
+        assert_err!(&event);
+
+        assert_eq!(
+            event.unwrap_err(),
+            ProcessResultError::AvailMatrixRowLength {
+                row: 1,
+                expected: 2,
+                found: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_process_names_and_matrix_timestamps_not_increasing() {
+        let names = vec!["Muneer".to_string().into_boxed_str()];
+
+        let avail_matrix = vec![
+            "1693746000,0".to_string().into_boxed_str(),
+            "1693746900,1".to_string().into_boxed_str(),
+            "1693746900,1".to_string().into_boxed_str(),
+        ];
+
+        let event = process_names_and_matrix(names, vec![94102857], avail_matrix);
+
+        assert_err!(&event);
+
+        assert_eq!(
+            event.unwrap_err(),
+            ProcessResultError::AvailMatrixTimestampNotIncreasing {
+                row: 2,
+                timestamp: "1693746900".to_string(),
+            }
+        );
+    }
 }
@@ -0,0 +1,287 @@
+use crate::fetch_availability::errors::{FetchError, HeadlessChromeError, ParseWhen2MeetError};
+use std::time::{Duration, Instant};
+
+/// How often, and for how long, a failing fetch is attempted again.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts made after the first one fails.
+    pub retries: u32,
+    /// How long the whole fetch, across all attempts, may take.
+    pub timeout: Duration,
+    /// How long a single navigation or evaluation may take.
+    pub step_timeout: Duration,
+    /// The wait before the first retry, doubled before every following one.
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            timeout: Duration::from_secs(120),
+            step_timeout: Duration::from_secs(20),
+            initial_backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// When a fetch has to be done by. Every step of it is given the step timeout, or only the time
+/// left if that is shorter, so no step runs past the deadline.
+#[derive(Clone, Copy, Debug)]
+pub struct Deadline {
+    /// `None` when the timeout is too long to represent, i.e. there is no deadline.
+    at: Option<Instant>,
+    step_timeout: Duration,
+}
+
+impl Deadline {
+    pub fn new(timeout: Duration, step_timeout: Duration) -> Self {
+        Self {
+            at: Instant::now().checked_add(timeout),
+            step_timeout,
+        }
+    }
+
+    /// The time left, zero once the deadline has passed.
+    pub fn remaining(&self) -> Duration {
+        match self.at {
+            Some(at) => at.saturating_duration_since(Instant::now()),
+            None => Duration::MAX,
+        }
+    }
+
+    pub fn has_passed(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// How long the next step may take.
+    pub fn step_timeout(&self) -> Duration {
+        self.step_timeout.min(self.remaining())
+    }
+}
+
+/// Whether another attempt could succeed, e.g. a crashed browser or a step that timed out, as
+/// opposed to a page that will never parse.
+pub fn is_transient(error: &ParseWhen2MeetError) -> bool {
+    matches!(
+        error,
+        ParseWhen2MeetError::HeadlessChrome(
+            HeadlessChromeError::FailedToLaunch(_)
+                | HeadlessChromeError::FailedToNewTab(_)
+                | HeadlessChromeError::FailedToNavigate { .. }
+                | HeadlessChromeError::FailedToWaitUntilNavigated { .. }
+        ) | ParseWhen2MeetError::Fetch(
+            FetchError::FailedEval { .. }
+                | FetchError::EvalNoValue { .. }
+                | FetchError::NotReady { .. }
+        )
+    )
+}
+
+/// Runs `attempt` until it succeeds, fails with an error that isn't transient, or the policy runs
+/// out of retries or time. Every attempt shares one deadline, which its steps must keep to. Errors
+/// after more than one attempt are wrapped with the attempt count.
+pub fn retry<T>(
+    policy: &RetryPolicy,
+    what: &str,
+    mut attempt: impl FnMut(&Deadline) -> Result<T, ParseWhen2MeetError>,
+) -> Result<T, ParseWhen2MeetError> {
+    let deadline = Deadline::new(policy.timeout, policy.step_timeout);
+    let mut backoff = policy.initial_backoff;
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        log::info!(
+            "Fetching {} (attempt {}/{})",
+            what,
+            attempts,
+            policy.retries + 1
+        );
+
+        let error = match attempt(&deadline) {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+
+        log::warn!("Attempt {} to fetch {} failed: {}", attempts, what, error);
+
+        // Whatever failed was likely cut short by the deadline.
+        if deadline.has_passed() {
+            return Err(ParseWhen2MeetError::TimedOut {
+                attempts,
+                timeout: policy.timeout,
+                source: Box::new(error),
+            });
+        }
+
+        if !is_transient(&error) || attempts > policy.retries {
+            return Err(match attempts {
+                1 => error,
+                _ => ParseWhen2MeetError::RetriesExhausted {
+                    attempts,
+                    source: Box::new(error),
+                },
+            });
+        }
+
+        if backoff >= deadline.remaining() {
+            return Err(ParseWhen2MeetError::TimedOut {
+                attempts,
+                timeout: policy.timeout,
+                source: Box::new(error),
+            });
+        }
+
+        log::info!("Retrying {} in {:?}", what, backoff);
+        std::thread::sleep(backoff);
+        backoff *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::errors::{FetchError, ParseError, ParseWhen2MeetError};
+    use crate::fetch_availability::retry::{retry, Deadline, RetryPolicy};
+    use claims::{assert_err, assert_ok};
+    use std::time::Duration;
+    use url::Url;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            retries: 2,
+            timeout: Duration::from_secs(10),
+            step_timeout: Duration::from_secs(1),
+            initial_backoff: Duration::from_millis(1),
+        }
+    }
+
+    fn transient_error() -> ParseWhen2MeetError {
+        ParseWhen2MeetError::Fetch(FetchError::EvalNoValue {
+            url: Url::parse("https://www.when2meet.com/?21240105-aBcDe").unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_retry_succeeds_after_transient_failures() {
+        let mut attempts = 0;
+
+        let result = retry(&policy(), "test", |_| {
+            attempts += 1;
+            match attempts {
+                3 => Ok(attempts),
+                _ => Err(transient_error()),
+            }
+        });
+
+        assert_ok!(&result);
+
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn test_retry_reports_attempts_when_exhausted() {
+        let mut attempts = 0;
+
+        let result: Result<(), _> = retry(&policy(), "test", |_| {
+            attempts += 1;
+            Err(transient_error())
+        });
+
+        assert_err!(&result);
+
+        let error = result.unwrap_err();
+
+        assert_eq!(attempts, 3);
+        assert!(matches!(
+            error,
+            ParseWhen2MeetError::RetriesExhausted { attempts: 3, .. }
+        ));
+        assert_eq!(error.to_string(), "Gave up after 3 attempts");
+    }
+
+    #[test]
+    fn test_retry_does_not_retry_permanent_errors() {
+        let mut attempts = 0;
+
+        let result: Result<(), _> = retry(&policy(), "test", |_| {
+            attempts += 1;
+            Err(ParseWhen2MeetError::Parse(ParseError::EmptyRaw))
+        });
+
+        assert_err!(&result);
+
+        assert_eq!(attempts, 1);
+        assert!(matches!(
+            result.unwrap_err(),
+            ParseWhen2MeetError::Parse(ParseError::EmptyRaw)
+        ));
+    }
+
+    #[test]
+    fn test_retry_stops_at_timeout() {
+        let policy = RetryPolicy {
+            retries: 10,
+            timeout: Duration::from_millis(50),
+            step_timeout: Duration::from_secs(1),
+            initial_backoff: Duration::from_millis(20),
+        };
+        let mut attempts = 0;
+
+        let result: Result<(), _> = retry(&policy, "test", |_| {
+            attempts += 1;
+            Err(transient_error())
+        });
+
+        assert_err!(&result);
+
+        assert!(attempts < 11);
+        assert!(matches!(
+            result.unwrap_err(),
+            ParseWhen2MeetError::TimedOut { attempts: reported, .. } if reported == attempts
+        ));
+    }
+
+    #[test]
+    fn test_retry_times_out_during_an_attempt() {
+        let policy = RetryPolicy {
+            timeout: Duration::from_millis(50),
+            ..policy()
+        };
+        let mut step_timeouts = Vec::new();
+
+        // A step that hangs until it is given up on, then fails in a way that isn't transient.
+        let result: Result<(), _> = retry(&policy, "test", |deadline| {
+            step_timeouts.push(deadline.step_timeout());
+            std::thread::sleep(deadline.step_timeout());
+            Err(ParseWhen2MeetError::Parse(ParseError::EmptyRaw))
+        });
+
+        assert_err!(&result);
+
+        assert_eq!(step_timeouts.len(), 1);
+        assert!(step_timeouts[0] <= Duration::from_millis(50));
+        assert!(matches!(
+            result.unwrap_err(),
+            ParseWhen2MeetError::TimedOut { attempts: 1, timeout, .. }
+                if timeout == Duration::from_millis(50)
+        ));
+    }
+
+    #[test]
+    fn test_deadline_step_timeout() {
+        let far = Deadline::new(Duration::from_secs(60), Duration::from_secs(5));
+        let near = Deadline::new(Duration::from_millis(10), Duration::from_secs(5));
+        let unbounded = Deadline::new(Duration::MAX, Duration::from_secs(5));
+
+        assert_eq!(far.step_timeout(), Duration::from_secs(5));
+        assert!(near.step_timeout() <= Duration::from_millis(10));
+        assert_eq!(unbounded.step_timeout(), Duration::from_secs(5));
+        assert!(!unbounded.has_passed());
+
+        std::thread::sleep(Duration::from_millis(10));
+
+        assert!(near.has_passed());
+        assert_eq!(near.step_timeout(), Duration::ZERO);
+    }
+}
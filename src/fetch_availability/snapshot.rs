@@ -0,0 +1,397 @@
+use crate::fetch_availability::errors::{ParseWhen2MeetError, ProcessResultError, SnapshotError};
+use crate::fetch_availability::model::{find_unrepresentable_slot, Event, EventMetadata, Person};
+use crate::fetch_availability::source::AvailabilitySource;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The raw arrays of a when2meet event as they were at `fetched_at`.
+///
+/// `avail_matrix` has one row per entry of `time_of_slot` and one column per entry of `people_ids`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub url: Option<Url>,
+    pub fetched_at: DateTime<Utc>,
+    #[serde(default, flatten)]
+    pub metadata: EventMetadata,
+    pub people_names: Vec<Box<str>>,
+    pub people_ids: Vec<i64>,
+    pub time_of_slot: Vec<i64>,
+    pub avail_matrix: Vec<Vec<bool>>,
+}
+
+impl Snapshot {
+    /// Builds a snapshot from the names, IDs and `<timestamp>,<0|1>,...` rows read off the page.
+    pub fn from_raw(
+        people_names: Vec<Box<str>>,
+        people_ids: Vec<i64>,
+        avail_matrix: Vec<Box<str>>,
+    ) -> Result<Self, ProcessResultError> {
+        let mut time_of_slot = Vec::with_capacity(avail_matrix.len());
+        let mut rows = Vec::with_capacity(avail_matrix.len());
+
+        for section in avail_matrix {
+            let mut parts = section.split(',');
+
+            let timestamp_str = match parts.next() {
+                Some(timestamp) => timestamp,
+                None => return Err(ProcessResultError::AvailMatrixNoNext { section }),
+            };
+
+            let timestamp = match timestamp_str.parse() {
+                Ok(timestamp) => timestamp,
+                Err(_) => {
+                    return Err(ProcessResultError::AvailMatrixFailedTimestampParse {
+                        timestamp: timestamp_str.to_string(),
+                    })
+                }
+            };
+
+            time_of_slot.push(timestamp);
+            rows.push(parts.map(|available| available == "1").collect());
+        }
+
+        let snapshot = Self {
+            version: SNAPSHOT_VERSION,
+            url: None,
+            fetched_at: Utc::now(),
+            metadata: EventMetadata::default(),
+            people_names,
+            people_ids,
+            time_of_slot,
+            avail_matrix: rows,
+        };
+
+        snapshot.validate()?;
+
+        Ok(snapshot)
+    }
+
+    /// Checks that there is an ID for every name, a row for every slot, one entry per person in
+    /// every row, and that slots are in strictly increasing order.
+    pub fn validate(&self) -> Result<(), ProcessResultError> {
+        if self.people_names.len() != self.people_ids.len() {
+            return Err(ProcessResultError::NamesIdsMismatch {
+                names: self.people_names.len(),
+                ids: self.people_ids.len(),
+            });
+        }
+
+        if self.time_of_slot.len() != self.avail_matrix.len() {
+            return Err(ProcessResultError::SlotsRowsMismatch {
+                slots: self.time_of_slot.len(),
+                rows: self.avail_matrix.len(),
+            });
+        }
+
+        for (row, available) in self.avail_matrix.iter().enumerate() {
+            if available.len() != self.people_ids.len() {
+                return Err(ProcessResultError::AvailMatrixRowLength {
+                    row,
+                    expected: self.people_ids.len(),
+                    found: available.len(),
+                });
+            }
+        }
+
+        for (row, pair) in self.time_of_slot.windows(2).enumerate() {
+            if pair[1] <= pair[0] {
+                return Err(ProcessResultError::AvailMatrixTimestampNotIncreasing {
+                    row: row + 1,
+                    timestamp: pair[1].to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn event(&self) -> Result<Event, ProcessResultError> {
+        self.validate()?;
+
+        let mut rows = Vec::with_capacity(self.time_of_slot.len());
+
+        for (timestamp, row) in self.time_of_slot.iter().zip(&self.avail_matrix) {
+            let start_time = match Utc.timestamp_opt(*timestamp, 0).single() {
+                Some(start_time) => start_time,
+                None => {
+                    return Err(ProcessResultError::AvailMatrixFailedTimestampParse {
+                        timestamp: timestamp.to_string(),
+                    })
+                }
+            };
+
+            let people = self
+                .people_names
+                .iter()
+                .zip(&self.people_ids)
+                .zip(row)
+                .map(|((name, id), available)| Person {
+                    id: id.to_string().into_boxed_str(),
+                    name: name.clone(),
+                    available: *available,
+                })
+                .collect();
+
+            rows.push((start_time, people));
+        }
+
+        let start_times: Vec<DateTime<Utc>> =
+            rows.iter().map(|(start_time, _)| *start_time).collect();
+
+        if let Some(row) = find_unrepresentable_slot(&start_times) {
+            return Err(ProcessResultError::SlotOutOfRange {
+                row,
+                timestamp: self.time_of_slot[row].to_string(),
+            });
+        }
+
+        Ok(Event::new(rows).with_metadata(self.metadata.clone()))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SnapshotError> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(source) => {
+                return Err(SnapshotError::FailedToRead {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+
+        let snapshot: Self = match serde_json::from_str(&json) {
+            Ok(snapshot) => snapshot,
+            Err(source) => return Err(SnapshotError::FailedToDeserialize(source)),
+        };
+
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion {
+                version: snapshot.version,
+            });
+        }
+
+        Ok(snapshot)
+    }
+
+    pub fn to_json(&self) -> Result<String, SnapshotError> {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => Ok(json),
+            Err(source) => Err(SnapshotError::FailedToSerialize(source)),
+        }
+    }
+}
+
+/// Replays a snapshot previously written by the `snapshot` subcommand.
+pub struct SnapshotSource {
+    pub path: PathBuf,
+}
+
+impl SnapshotSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl AvailabilitySource for SnapshotSource {
+    fn fetch(&self) -> Result<Event, ParseWhen2MeetError> {
+        let snapshot = match Snapshot::read(&self.path) {
+            Ok(snapshot) => snapshot,
+            Err(snapshot_error) => return Err(ParseWhen2MeetError::Snapshot(snapshot_error)),
+        };
+
+        match snapshot.event() {
+            Ok(event) => Ok(event),
+            Err(process_result_error) => {
+                Err(ParseWhen2MeetError::ProcessResult(process_result_error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::errors::{ProcessResultError, SnapshotError};
+    use crate::fetch_availability::parse::process_names_and_matrix;
+    use crate::fetch_availability::snapshot::{Snapshot, SnapshotSource, SNAPSHOT_VERSION};
+    use crate::fetch_availability::source::AvailabilitySource;
+    use claims::{assert_err, assert_ok};
+
+    fn names() -> Vec<Box<str>> {
+        vec![
+            "Muneer".to_string().into_boxed_str(),
+            "Brian".to_string().into_boxed_str(),
+            "Garrett".to_string().into_boxed_str(),
+        ]
+    }
+
+    fn avail_matrix() -> Vec<Box<str>> {
+        vec![
+            "1693746000,0,0,0".to_string().into_boxed_str(),
+            "1693746900,1,0,0".to_string().into_boxed_str(),
+            "1693747800,0,1,0".to_string().into_boxed_str(),
+        ]
+    }
+
+    #[test]
+    fn test_from_raw() {
+        let snapshot = Snapshot::from_raw(names(), vec![1, 2, 3], avail_matrix());
+
+        assert_ok!(&snapshot);
+
+        let snapshot = snapshot.unwrap();
+
+        assert_eq!(snapshot.version, SNAPSHOT_VERSION);
+        assert_eq!(
+            snapshot.time_of_slot,
+            vec![1693746000, 1693746900, 1693747800]
+        );
+        assert_eq!(
+            snapshot.avail_matrix,
+            vec![
+                vec![false, false, false],
+                vec![true, false, false],
+                vec![false, true, false],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_raw_bad_timestamp() {
+        let snapshot = Snapshot::from_raw(
+            names(),
+            vec![1, 2, 3],
+            vec!["tomorrow,0,0,0".to_string().into_boxed_str()],
+        );
+
+        assert_err!(&snapshot);
+
+        assert_eq!(
+            snapshot.unwrap_err(),
+            ProcessResultError::AvailMatrixFailedTimestampParse {
+                timestamp: "tomorrow".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_raw_short_row() {
+        let mut avail_matrix = avail_matrix();
+        avail_matrix[2] = "1693747800,0,1".to_string().into_boxed_str();
+
+        let snapshot = Snapshot::from_raw(names(), vec![1, 2, 3], avail_matrix);
+
+        assert_err!(&snapshot);
+
+        assert_eq!(
+            snapshot.unwrap_err(),
+            ProcessResultError::AvailMatrixRowLength {
+                row: 2,
+                expected: 3,
+                found: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_event_edited_snapshot_out_of_order() {
+        let mut snapshot = Snapshot::from_raw(names(), vec![1, 2, 3], avail_matrix()).unwrap();
+        snapshot.time_of_slot.swap(1, 2);
+
+        let event = snapshot.event();
+
+        assert_err!(&event);
+
+        assert_eq!(
+            event.unwrap_err(),
+            ProcessResultError::AvailMatrixTimestampNotIncreasing {
+                row: 2,
+                timestamp: "1693746900".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_event_edited_snapshot_missing_row() {
+        let mut snapshot = Snapshot::from_raw(names(), vec![1, 2, 3], avail_matrix()).unwrap();
+        snapshot.avail_matrix.pop();
+
+        let event = snapshot.event();
+
+        assert_err!(&event);
+
+        assert_eq!(
+            event.unwrap_err(),
+            ProcessResultError::SlotsRowsMismatch { slots: 3, rows: 2 }
+        );
+    }
+
+    #[test]
+    fn test_event_edited_snapshot_out_of_range() {
+        let mut snapshot = Snapshot::from_raw(names(), vec![1, 2, 3], avail_matrix()).unwrap();
+        snapshot.time_of_slot[2] = 8210298412799;
+
+        let event = snapshot.event();
+
+        assert_err!(&event);
+
+        assert_eq!(
+            event.unwrap_err(),
+            ProcessResultError::SlotOutOfRange {
+                row: 2,
+                timestamp: "8210298412799".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_event_matches_process_names_and_matrix() {
+        let snapshot = Snapshot::from_raw(names(), vec![1, 2, 3], avail_matrix()).unwrap();
+
+        assert_eq!(
+            snapshot.event().unwrap(),
+            process_names_and_matrix(names(), vec![1, 2, 3], avail_matrix()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let snapshot = Snapshot::from_raw(names(), vec![1, 2, 3], avail_matrix()).unwrap();
+        let path = std::env::temp_dir().join("when3meet_test_snapshot_round_trip.json");
+
+        std::fs::write(&path, snapshot.to_json().unwrap()).unwrap();
+
+        let read = Snapshot::read(&path);
+        let event = SnapshotSource::new(path.clone()).fetch();
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_ok!(&read);
+        assert_eq!(read.unwrap(), snapshot);
+        assert_eq!(event.unwrap(), snapshot.event().unwrap());
+    }
+
+    #[test]
+    fn test_read_unsupported_version() {
+        let mut snapshot = Snapshot::from_raw(names(), vec![1, 2, 3], avail_matrix()).unwrap();
+        snapshot.version = SNAPSHOT_VERSION + 1;
+        let path = std::env::temp_dir().join("when3meet_test_snapshot_unsupported_version.json");
+
+        std::fs::write(&path, snapshot.to_json().unwrap()).unwrap();
+
+        let read = Snapshot::read(&path);
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_err!(&read);
+
+        assert!(matches!(
+            read.unwrap_err(),
+            SnapshotError::UnsupportedVersion { version } if version == SNAPSHOT_VERSION + 1
+        ));
+    }
+}
@@ -0,0 +1,334 @@
+use crate::fetch_availability::chrome::ChromeOptions;
+use crate::fetch_availability::errors::{FileError, ParseWhen2MeetError};
+use crate::fetch_availability::html::{is_event_page, parse_when2meet_html, parse_when2meet_http};
+use crate::fetch_availability::import::{parse_availability_csv, parse_availability_json};
+use crate::fetch_availability::model::Event;
+use crate::fetch_availability::parse::parse_when2meet;
+use crate::fetch_availability::validate::validate_when2meet_url;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Anything that can produce the availability grid of an event.
+///
+/// Implement this to plug a new backend into the rest of the pipeline.
+pub trait AvailabilitySource {
+    fn fetch(&self) -> Result<Event, ParseWhen2MeetError>;
+}
+
+/// Loads the when2meet page in headless Chrome and reads the availability globals.
+pub struct ChromeSource {
+    pub url: Url,
+    pub options: ChromeOptions,
+}
+
+impl ChromeSource {
+    pub fn new(url: Url, options: ChromeOptions) -> Self {
+        Self { url, options }
+    }
+}
+
+impl AvailabilitySource for ChromeSource {
+    fn fetch(&self) -> Result<Event, ParseWhen2MeetError> {
+        if let Err(invalid_url_error) = validate_when2meet_url(&self.url) {
+            return Err(ParseWhen2MeetError::InvalidUrl(invalid_url_error));
+        }
+
+        parse_when2meet(&self.url, &self.options)
+    }
+}
+
+/// Downloads the when2meet page over plain HTTP and extracts the availability from its HTML.
+pub struct HttpSource {
+    pub url: Url,
+}
+
+impl HttpSource {
+    pub fn new(url: Url) -> Self {
+        Self { url }
+    }
+}
+
+impl AvailabilitySource for HttpSource {
+    fn fetch(&self) -> Result<Event, ParseWhen2MeetError> {
+        if let Err(invalid_url_error) = validate_when2meet_url(&self.url) {
+            return Err(ParseWhen2MeetError::InvalidUrl(invalid_url_error));
+        }
+
+        parse_when2meet_http(&self.url)
+    }
+}
+
+/// Reads a when2meet page previously saved to disk.
+pub struct FileSource {
+    pub path: PathBuf,
+}
+
+impl FileSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl AvailabilitySource for FileSource {
+    fn fetch(&self) -> Result<Event, ParseWhen2MeetError> {
+        let html = read_file(&self.path)?;
+
+        if !is_event_page(&html) {
+            return Err(ParseWhen2MeetError::NotAnEvent {
+                location: self.path.display().to_string().into_boxed_str(),
+            });
+        }
+
+        parse_when2meet_html(&html)
+    }
+}
+
+/// Reads a CSV grid of a `timestamp` column and one column per person.
+pub struct CsvSource {
+    pub path: PathBuf,
+}
+
+impl CsvSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl AvailabilitySource for CsvSource {
+    fn fetch(&self) -> Result<Event, ParseWhen2MeetError> {
+        parse_availability_csv(&read_file(&self.path)?)
+    }
+}
+
+/// Reads availability in the JSON schema documented on `AvailabilityJson`.
+pub struct JsonSource {
+    pub path: PathBuf,
+}
+
+impl JsonSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl AvailabilitySource for JsonSource {
+    fn fetch(&self) -> Result<Event, ParseWhen2MeetError> {
+        parse_availability_json(&read_file(&self.path)?)
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, ParseWhen2MeetError> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(source) => Err(ParseWhen2MeetError::File(FileError::FailedToRead {
+            path: path.to_path_buf(),
+            source,
+        })),
+    }
+}
+
+/// Fetches several events concurrently and merges them into one grid with `Event::merge`.
+pub struct MergedSource {
+    pub sources: Vec<Box<dyn AvailabilitySource + Send + Sync>>,
+}
+
+impl MergedSource {
+    pub fn new(sources: Vec<Box<dyn AvailabilitySource + Send + Sync>>) -> Self {
+        Self { sources }
+    }
+}
+
+impl AvailabilitySource for MergedSource {
+    fn fetch(&self) -> Result<Event, ParseWhen2MeetError> {
+        let results: Vec<Result<Event, ParseWhen2MeetError>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .sources
+                .iter()
+                .map(|source| scope.spawn(move || source.fetch()))
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| match handle.join() {
+                    Ok(result) => result,
+                    Err(panic) => std::panic::resume_unwind(panic),
+                })
+                .collect()
+        });
+
+        let mut events = Vec::with_capacity(results.len());
+
+        for result in results {
+            events.push(result?);
+        }
+
+        match Event::merge(events) {
+            Ok(event) => Ok(event),
+            Err(process_result_error) => {
+                Err(ParseWhen2MeetError::ProcessResult(process_result_error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::errors::{
+        FileError, InvalidUrlError, ParseWhen2MeetError, ProcessResultError,
+    };
+    use crate::fetch_availability::html::parse_when2meet_html;
+    use crate::fetch_availability::model::Event;
+    use crate::fetch_availability::source::{
+        AvailabilitySource, CsvSource, FileSource, HttpSource, MergedSource,
+    };
+    use claims::{assert_err, assert_ok};
+    use std::path::PathBuf;
+    use url::Url;
+
+    const FIXTURE_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/when2meet_event.html"
+    );
+
+    const DAYS_OF_WEEK_FIXTURE_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/when2meet_days_of_week.html"
+    );
+
+    const CSV_FIXTURE_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/availability.csv"
+    );
+
+    struct StaticSource;
+
+    impl AvailabilitySource for StaticSource {
+        fn fetch(&self) -> Result<Event, ParseWhen2MeetError> {
+            Err(ParseWhen2MeetError::Custom("unreachable backend".into()))
+        }
+    }
+
+    #[test]
+    fn test_file_source() {
+        let source = FileSource::new(PathBuf::from(FIXTURE_PATH));
+
+        let event = source.fetch();
+
+        assert_ok!(&event);
+
+        let html = std::fs::read_to_string(FIXTURE_PATH).unwrap();
+
+        assert_eq!(event.unwrap(), parse_when2meet_html(&html).unwrap());
+    }
+
+    #[test]
+    fn test_file_source_missing_file() {
+        let source = FileSource::new(PathBuf::from("does/not/exist.html"));
+
+        let event = source.fetch();
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::File(FileError::FailedToRead { path, .. }) if path.ends_with("exist.html")
+        ));
+    }
+
+    #[test]
+    fn test_csv_source_missing_file() {
+        let source = CsvSource::new(PathBuf::from("does/not/exist.csv"));
+
+        let event = source.fetch();
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::File(FileError::FailedToRead { path, .. }) if path.ends_with("exist.csv")
+        ));
+    }
+
+    #[test]
+    fn test_custom_source_as_trait_object() {
+        let source: Box<dyn AvailabilitySource> = Box::new(StaticSource);
+
+        let event = source.fetch();
+
+        assert_err!(&event);
+
+        assert_eq!(event.unwrap_err().to_string(), "unreachable backend");
+    }
+
+    #[test]
+    fn test_http_source_rejects_non_when2meet_url() {
+        let source = HttpSource::new(Url::parse("https://example.com/?21240105-aBcDe").unwrap());
+
+        let event = source.fetch();
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::InvalidUrl(InvalidUrlError::NotWhen2Meet { .. })
+        ));
+    }
+
+    #[test]
+    fn test_merged_source() {
+        let source = MergedSource::new(vec![
+            Box::new(FileSource::new(PathBuf::from(FIXTURE_PATH))),
+            Box::new(CsvSource::new(PathBuf::from(CSV_FIXTURE_PATH))),
+        ]);
+
+        let event = source.fetch();
+
+        assert_ok!(&event);
+
+        let event = event.unwrap();
+        let first = FileSource::new(PathBuf::from(FIXTURE_PATH))
+            .fetch()
+            .unwrap();
+        let second = CsvSource::new(PathBuf::from(CSV_FIXTURE_PATH))
+            .fetch()
+            .unwrap();
+
+        assert_eq!(event, Event::merge(vec![first, second]).unwrap());
+        assert_eq!(event.title, Some("Team Sync".to_string().into_boxed_str()));
+        assert_eq!(event.participants.len(), 3);
+    }
+
+    #[test]
+    fn test_merged_source_rejects_mixed_event_modes() {
+        let source = MergedSource::new(vec![
+            Box::new(FileSource::new(PathBuf::from(FIXTURE_PATH))),
+            Box::new(FileSource::new(PathBuf::from(DAYS_OF_WEEK_FIXTURE_PATH))),
+        ]);
+
+        let event = source.fetch();
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::ProcessResult(ProcessResultError::MixedEventModes)
+        ));
+    }
+
+    #[test]
+    fn test_merged_source_fails_if_any_source_fails() {
+        let source = MergedSource::new(vec![
+            Box::new(FileSource::new(PathBuf::from(FIXTURE_PATH))),
+            Box::new(FileSource::new(PathBuf::from("does/not/exist.html"))),
+        ]);
+
+        let event = source.fetch();
+
+        assert_err!(&event);
+
+        assert!(matches!(
+            event.unwrap_err(),
+            ParseWhen2MeetError::File(FileError::FailedToRead { .. })
+        ));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::fetch_availability::errors::InvalidUrlError;
+use url::Url;
+
+/// Checks that `url` is a when2meet event link of the form `https://www.when2meet.com/?<id>-<code>`
+/// before anything is fetched.
+pub fn validate_when2meet_url(url: &Url) -> Result<(), InvalidUrlError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(InvalidUrlError::NotWhen2Meet { url: url.clone() });
+    }
+
+    match url.host_str() {
+        Some("when2meet.com") | Some("www.when2meet.com") => {}
+        _ => return Err(InvalidUrlError::NotWhen2Meet { url: url.clone() }),
+    }
+
+    let event = match url.query() {
+        Some(event) if !event.is_empty() => event,
+        _ => return Err(InvalidUrlError::MissingEvent { url: url.clone() }),
+    };
+
+    let valid = match event.split_once('-') {
+        Some((id, code)) => {
+            !id.is_empty()
+                && id.chars().all(|c| c.is_ascii_digit())
+                && !code.is_empty()
+                && code.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+        None => false,
+    };
+
+    if !valid {
+        return Err(InvalidUrlError::MalformedEvent {
+            url: url.clone(),
+            event: event.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Parses and validates a when2meet event URL, for use as a clap value parser.
+pub fn parse_when2meet_url(raw_url: &str) -> Result<Url, InvalidUrlError> {
+    let url = match Url::parse(raw_url) {
+        Ok(url) => url,
+        Err(source) => return Err(InvalidUrlError::Unparseable(source)),
+    };
+
+    validate_when2meet_url(&url)?;
+
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::errors::InvalidUrlError;
+    use crate::fetch_availability::validate::parse_when2meet_url;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn test_parse_when2meet_url_valid() {
+        assert_ok!(parse_when2meet_url(
+            "https://www.when2meet.com/?21240105-aBcDe"
+        ));
+        assert_ok!(parse_when2meet_url("http://when2meet.com/?21240105-aBcDe"));
+    }
+
+    #[test]
+    fn test_parse_when2meet_url_unparseable() {
+        let url = parse_when2meet_url("when2meet.com/?21240105-aBcDe");
+
+        assert_err!(&url);
+
+        assert!(matches!(url.unwrap_err(), InvalidUrlError::Unparseable(_)));
+    }
+
+    #[test]
+    fn test_parse_when2meet_url_other_host() {
+        let url = parse_when2meet_url("https://www.lettucemeet.com/?21240105-aBcDe");
+
+        assert_err!(&url);
+
+        assert!(matches!(
+            url.unwrap_err(),
+            InvalidUrlError::NotWhen2Meet { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_when2meet_url_homepage() {
+        let url = parse_when2meet_url("https://www.when2meet.com/");
+
+        assert_err!(&url);
+
+        assert!(matches!(
+            url.unwrap_err(),
+            InvalidUrlError::MissingEvent { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_when2meet_url_malformed_event() {
+        for raw_url in [
+            "https://www.when2meet.com/?21240105",
+            "https://www.when2meet.com/?21240105-",
+            "https://www.when2meet.com/?2124O105-aBcDe",
+            "https://www.when2meet.com/?21240105-aBc/De",
+        ] {
+            let url = parse_when2meet_url(raw_url);
+
+            assert_err!(&url);
+
+            assert!(matches!(
+                url.unwrap_err(),
+                InvalidUrlError::MalformedEvent { .. }
+            ));
+        }
+    }
+}
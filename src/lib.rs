@@ -1,4 +1,5 @@
 pub mod cli;
 pub mod fetch_availability;
+pub mod manage_event;
 pub mod optimal;
 pub mod output;
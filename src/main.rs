@@ -2,27 +2,154 @@ use anyhow::{Context, Result};
 use clap::Parser;
 use std::fs::File;
 use std::io;
-use when3meet::cli::args::Args;
-use when3meet::fetch_availability::parse_when2meet;
+use std::io::Write;
+use when3meet::cli::args::{Args, Command, CreateArgs, RespondArgs, SnapshotArgs};
+use when3meet::cli::config::Config;
+use when3meet::fetch_availability::{
+    snapshot_when2meet, snapshot_when2meet_http, Aliases, CalDavClient, Calendar,
+};
+use when3meet::manage_event::{create_when2meet_event, respond_to_when2meet, Availability};
 use when3meet::optimal::find_opt;
-use when3meet::output::write_slots;
+use when3meet::output::write_event;
+
+/// Exit status when the event exists but nobody has filled it in yet.
+const NO_RESPONSES_EXIT_CODE: i32 = 3;
 
 fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
     let args = Args::parse();
-    let slots = parse_when2meet(&args.when2meet_url)?;
-    let slots = find_opt(&slots, &args.required_people, &args.flexible_naming);
+
+    match args.command {
+        Some(Command::Snapshot(snapshot_args)) => return snapshot(snapshot_args),
+        Some(Command::Create(create_args)) => return create(create_args),
+        Some(Command::Respond(respond_args)) => return respond(respond_args),
+        None => {}
+    }
+
+    let config = Config::load(args.config.as_deref())?;
+    let now = chrono::Utc::now();
+    let mut event = args.source(&config).fetch()?;
+
+    if event.participants.is_empty() {
+        eprintln!("No responses yet");
+        std::process::exit(NO_RESPONSES_EXIT_CODE);
+    }
+
+    for (name, path) in &args.ics {
+        let calendar = Calendar::read(path).with_context(|| {
+            format!(
+                "Failed to load the calendar of {} from {}",
+                name,
+                path.display()
+            )
+        })?;
+        event = event.with_calendar(name, &calendar, now);
+    }
+
+    let calendars = args.caldav_calendars(&config);
+    if !calendars.is_empty() {
+        if let Some((from, to)) = event.calendar_range(now) {
+            let client = CalDavClient::new(config.caldav.credentials());
+
+            for (name, url) in &calendars {
+                let calendar = client.free_busy(url, from, to).with_context(|| {
+                    format!("Failed to fetch the free/busy of {} from {}", name, url)
+                })?;
+                event = if args.caldav_veto {
+                    event.without_busy(&calendar, now)
+                } else {
+                    event.with_calendar(name, &calendar, now)
+                };
+            }
+        }
+    }
+    let (event, required_people) = match args.aliases.as_ref().or(config.aliases.as_ref()) {
+        Some(path) => {
+            let aliases = Aliases::read(path)
+                .with_context(|| format!("Failed to load aliases from {}", path.display()))?;
+            let required_people = args
+                .required_people
+                .iter()
+                .map(|name| aliases.canonical(name).unwrap_or(name).to_string())
+                .collect();
+            (event.with_aliases(&aliases), required_people)
+        }
+        None => (event, args.required_people.clone()),
+    };
+
+    let slots = find_opt(&event.slots, &required_people, &args.flexible_naming)?;
 
     match args.output_file_path {
         Some(path) => {
             let mut file = File::create(path).context("Failed to create output file")?;
-            write_slots(&slots, &mut file)?;
+            write_event(&event, &slots, &mut file)?;
         }
         None => {
             let stdout = io::stdout();
             let mut handle = stdout.lock();
-            write_slots(&slots, &mut handle)?;
+            write_event(&event, &slots, &mut handle)?;
         }
     }
 
     Ok(())
 }
+
+fn snapshot(args: SnapshotArgs) -> Result<()> {
+    let snapshot = if args.no_browser {
+        snapshot_when2meet_http(&args.when2meet_url)?
+    } else {
+        let config = Config::load(args.config.as_deref())?;
+        snapshot_when2meet(&args.when2meet_url, &args.chrome.options(&config))?
+    };
+    let json = snapshot.to_json()?;
+
+    match args.output_file_path {
+        Some(path) => {
+            let mut file = File::create(path).context("Failed to create snapshot file")?;
+            writeln!(file, "{}", json).context("Failed to write snapshot")?;
+        }
+        None => {
+            let stdout = io::stdout();
+            let mut handle = stdout.lock();
+            writeln!(handle, "{}", json).context("Failed to write snapshot")?;
+        }
+    }
+
+    Ok(())
+}
+
+fn create(args: CreateArgs) -> Result<()> {
+    let url = create_when2meet_event(&args.base_url, &args.new_event())
+        .context("Failed to create the event")?;
+    println!("{}", url);
+
+    Ok(())
+}
+
+fn respond(args: RespondArgs) -> Result<()> {
+    let config = Config::load(args.config.as_deref())?;
+    let availability = match &args.ics {
+        Some(path) => Availability::Calendar(
+            Calendar::read(path)
+                .with_context(|| format!("Failed to load the calendar from {}", path.display()))?,
+        ),
+        None => Availability::Ranges(args.slots.clone()),
+    };
+
+    let outcome = respond_to_when2meet(
+        &args.when2meet_url,
+        &args.chrome.options(&config),
+        &args.respondent(),
+        &availability,
+        chrono::Utc::now(),
+    )
+    .with_context(|| format!("Failed to respond as {}", args.name))?;
+
+    println!(
+        "Marked {} slots available as {}, toggling {}",
+        outcome.available, args.name, outcome.toggled
+    );
+
+    Ok(())
+}
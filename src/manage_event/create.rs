@@ -0,0 +1,299 @@
+use crate::manage_event::errors::CreateEventError;
+use chrono::{NaiveDate, Weekday};
+use chrono_tz::Tz;
+use url::Url;
+
+pub const WHEN2MEET_BASE_URL: &str = "https://www.when2meet.com";
+
+/// The days a new event asks about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventDays {
+    Dates(Vec<NaiveDate>),
+    Weekdays(Vec<Weekday>),
+}
+
+/// What when2meet's event creation form asks for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewEvent {
+    pub title: String,
+    pub days: EventDays,
+    /// The hour the grid starts at each day, from 0 to 23.
+    pub earliest_hour: u32,
+    /// The hour the grid ends at each day, up to 24 for midnight.
+    pub latest_hour: u32,
+    pub timezone: Tz,
+}
+
+impl NewEvent {
+    pub fn validate(&self) -> Result<(), CreateEventError> {
+        let no_days = match &self.days {
+            EventDays::Dates(dates) => dates.is_empty(),
+            EventDays::Weekdays(weekdays) => weekdays.is_empty(),
+        };
+
+        if no_days {
+            return Err(CreateEventError::NoDays);
+        }
+
+        if self.earliest_hour > 23
+            || self.latest_hour > 24
+            || self.latest_hour <= self.earliest_hour
+        {
+            return Err(CreateEventError::InvalidHours {
+                earliest: self.earliest_hour,
+                latest: self.latest_hour,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// The fields of the form, with dates as `YYYY-MM-DD` and weekdays as numbers from 0 for
+    /// Sunday, each list joined with `|`.
+    pub fn form(&self) -> Vec<(&'static str, String)> {
+        let (date_types, possible_dates) = match &self.days {
+            EventDays::Dates(dates) => (
+                "SpecificDates",
+                dates
+                    .iter()
+                    .map(|date| date.format("%Y-%m-%d").to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            EventDays::Weekdays(weekdays) => (
+                "DaysOfTheWeek",
+                weekdays
+                    .iter()
+                    .map(|weekday| weekday.num_days_from_sunday().to_string())
+                    .collect(),
+            ),
+        };
+
+        vec![
+            ("NewEventName", self.title.clone()),
+            ("DateTypes", date_types.to_string()),
+            ("PossibleDates", possible_dates.join("|")),
+            ("NoEarlierThan", self.earliest_hour.to_string()),
+            ("NoLaterThan", self.latest_hour.to_string()),
+            ("TimeZone", self.timezone.name().to_string()),
+        ]
+    }
+}
+
+/// Submits `event` to the creation form of the when2meet at `base_url` and returns the new event's
+/// link.
+pub fn create_when2meet_event(base_url: &Url, event: &NewEvent) -> Result<Url, CreateEventError> {
+    event.validate()?;
+
+    let url = match base_url.join("/SaveNewEvent.php") {
+        Ok(url) => url,
+        Err(source) => {
+            return Err(CreateEventError::InvalidBaseUrl {
+                url: base_url.clone(),
+                source,
+            })
+        }
+    };
+
+    let form = event.form();
+    let form: Vec<(&str, &str)> = form
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .collect();
+
+    let response = match ureq::post(url.as_str()).send_form(&form) {
+        Ok(response) => response,
+        Err(source) => {
+            return Err(CreateEventError::FailedRequest {
+                url,
+                source: Box::new(source),
+            })
+        }
+    };
+
+    let body = match response.into_string() {
+        Ok(body) => body,
+        Err(source) => return Err(CreateEventError::FailedToReadBody { url, source }),
+    };
+
+    match extract_event(&body) {
+        Some(event) => {
+            let mut event_url = base_url.clone();
+            event_url.set_path("/");
+            event_url.set_query(Some(event));
+            Ok(event_url)
+        }
+        None => Err(CreateEventError::MissingEventUrl { url }),
+    }
+}
+
+/// The `<id>-<code>` of the event the response redirects to, e.g. from
+/// `window.location='./?21240105-aBcDe'`.
+fn extract_event(body: &str) -> Option<&str> {
+    body.match_indices('?').find_map(|(index, _)| {
+        let rest = &body[index + 1..];
+        let id_length = rest.find(|c: char| !c.is_ascii_digit())?;
+        let code = rest[id_length..].strip_prefix('-')?;
+        let code_length = code
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(code.len());
+
+        if id_length > 0 && code_length > 0 {
+            Some(&rest[..id_length + 1 + code_length])
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::manage_event::create::{create_when2meet_event, extract_event, EventDays, NewEvent};
+    use crate::manage_event::errors::CreateEventError;
+    use chrono::{NaiveDate, Weekday};
+    use claims::{assert_err, assert_ok};
+    use std::thread;
+    use url::Url;
+
+    fn new_event(days: EventDays) -> NewEvent {
+        NewEvent {
+            title: "Team Sync".to_string(),
+            days,
+            earliest_hour: 9,
+            latest_hour: 17,
+            timezone: "America/New_York".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_create_when2meet_event() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base_url = Url::parse(&format!("http://{}", server.server_addr())).unwrap();
+
+        let handle = thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let seen = (
+                request.method().to_string(),
+                request.url().to_string(),
+                body,
+            );
+            request
+                .respond(tiny_http::Response::from_string(
+                    "<body onload=\"window.location='./?21240105-aBcDe'\"></body>",
+                ))
+                .unwrap();
+            seen
+        });
+
+        let url = create_when2meet_event(
+            &base_url,
+            &new_event(EventDays::Dates(vec![
+                NaiveDate::from_ymd_opt(2023, 9, 3).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 9, 4).unwrap(),
+            ])),
+        );
+
+        let (method, path, body) = handle.join().unwrap();
+
+        assert_ok!(&url);
+
+        assert_eq!(method, "POST");
+        assert_eq!(path, "/SaveNewEvent.php");
+        assert_eq!(
+            body,
+            "NewEventName=Team+Sync&DateTypes=SpecificDates&PossibleDates=2023-09-03%7C2023-09-04&NoEarlierThan=9&NoLaterThan=17&TimeZone=America%2FNew_York"
+        );
+        assert_eq!(url.unwrap(), base_url.join("/?21240105-aBcDe").unwrap());
+    }
+
+    #[test]
+    fn test_create_when2meet_event_without_link() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base_url = Url::parse(&format!("http://{}", server.server_addr())).unwrap();
+
+        let handle = thread::spawn(move || {
+            let request = server.recv().unwrap();
+            request
+                .respond(tiny_http::Response::from_string("Please try again later."))
+                .unwrap();
+        });
+
+        let url = create_when2meet_event(
+            &base_url,
+            &new_event(EventDays::Weekdays(vec![Weekday::Mon])),
+        );
+
+        handle.join().unwrap();
+
+        assert_err!(&url);
+
+        assert!(matches!(
+            url.unwrap_err(),
+            CreateEventError::MissingEventUrl { .. }
+        ));
+    }
+
+    #[test]
+    fn test_create_when2meet_event_invalid_base_url() {
+        let base_url = Url::parse("mailto:someone@example.com").unwrap();
+
+        let url = create_when2meet_event(
+            &base_url,
+            &new_event(EventDays::Weekdays(vec![Weekday::Mon])),
+        );
+
+        assert_err!(&url);
+
+        assert!(matches!(
+            url.unwrap_err(),
+            CreateEventError::InvalidBaseUrl { url, .. } if url == base_url
+        ));
+    }
+
+    #[test]
+    fn test_form_weekdays() {
+        let form = new_event(EventDays::Weekdays(vec![
+            Weekday::Sun,
+            Weekday::Mon,
+            Weekday::Sat,
+        ]))
+        .form();
+
+        assert!(form.contains(&("DateTypes", "DaysOfTheWeek".to_string())));
+        assert!(form.contains(&("PossibleDates", "0|1|6".to_string())));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_ok!(new_event(EventDays::Weekdays(vec![Weekday::Mon])).validate());
+
+        assert!(matches!(
+            new_event(EventDays::Dates(Vec::new())).validate(),
+            Err(CreateEventError::NoDays)
+        ));
+
+        for (earliest_hour, latest_hour) in [(17, 9), (9, 9), (24, 24), (9, 25)] {
+            let event = NewEvent {
+                earliest_hour,
+                latest_hour,
+                ..new_event(EventDays::Weekdays(vec![Weekday::Mon]))
+            };
+
+            assert!(matches!(
+                event.validate(),
+                Err(CreateEventError::InvalidHours { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_extract_event() {
+        assert_eq!(
+            extract_event("<script>window.location='/?21240105-aBcDe';</script>"),
+            Some("21240105-aBcDe")
+        );
+        assert_eq!(extract_event("Is this right? 12 - no"), None);
+        assert_eq!(extract_event("?21240105-"), None);
+    }
+}
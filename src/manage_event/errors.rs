@@ -0,0 +1,68 @@
+use crate::fetch_availability::errors::{FetchError, ParseWhen2MeetError};
+use thiserror::Error;
+use url::Url;
+
+#[derive(Error, Debug)]
+pub enum CreateEventError {
+    #[error("An event needs at least one date or weekday")]
+    NoDays,
+    #[error("The event must start between 0 and 23 o'clock and end after it starts, by 24 o'clock; got {earliest} to {latest}")]
+    InvalidHours { earliest: u32, latest: u32 },
+    #[error("Can't build the event creation URL from {url}")]
+    InvalidBaseUrl {
+        url: Url,
+        #[source]
+        source: url::ParseError,
+    },
+    #[error("Failed to submit the new event to {url}")]
+    FailedRequest {
+        url: Url,
+        #[source]
+        source: Box<ureq::Error>,
+    },
+    #[error("Failed to read the response of {url}")]
+    FailedToReadBody {
+        url: Url,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{url} did not respond with a link to the new event")]
+    MissingEventUrl { url: Url },
+}
+
+#[derive(Error, Debug)]
+pub enum RespondError {
+    #[error("Invalid slot range '{range}'; expected a date or weekday and a time range, e.g. '2023-09-03 13:00-14:30' or 'mon 9:00-17:00'")]
+    InvalidSlotRange { range: String },
+    #[error("The slot range '{range}' matches no slot of the event; check the dates, weekdays and time zone")]
+    UnmatchedSlotRange { range: Box<str> },
+    #[error(transparent)]
+    When2Meet(ParseWhen2MeetError),
+    #[error(transparent)]
+    Fetch(FetchError),
+    #[error("Failed to read the result of {what} on {url}: {value}")]
+    UnexpectedResult {
+        url: Url,
+        what: &'static str,
+        value: Box<str>,
+    },
+    #[error("{url} rejected signing in as {name}: {message}")]
+    SignInRejected {
+        url: Url,
+        name: Box<str>,
+        message: Box<str>,
+    },
+    #[error("Signing in as {name} on {url} did not finish within {waited:?}")]
+    SignInTimedOut {
+        url: Url,
+        name: Box<str>,
+        waited: std::time::Duration,
+    },
+    #[error("The grid on {url} has no cell for {missing} of the slots")]
+    MissingCells { url: Url, missing: usize },
+    #[error("Saving the availability on {url} did not finish within {waited:?}")]
+    SaveTimedOut {
+        url: Url,
+        waited: std::time::Duration,
+    },
+}
@@ -0,0 +1,8 @@
+pub mod create;
+pub mod errors;
+pub mod respond;
+
+pub use create::{create_when2meet_event, EventDays, NewEvent, WHEN2MEET_BASE_URL};
+pub use respond::{
+    respond_to_when2meet, Availability, RangeDay, RespondOutcome, Respondent, SlotRange,
+};
@@ -0,0 +1,588 @@
+use crate::fetch_availability::chrome::ChromeOptions;
+use crate::fetch_availability::ics::Calendar;
+use crate::fetch_availability::model::{Event, Recurrence};
+use crate::fetch_availability::parse::{evaluate, open_when2meet, read_when2meet};
+use crate::fetch_availability::retry::Deadline;
+use crate::manage_event::errors::RespondError;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use headless_chrome::Tab;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use url::Url;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The day a slot range is on: a date for a specific dates event, a weekday for a days of the
+/// week one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RangeDay {
+    Date(NaiveDate),
+    Weekday(Weekday),
+}
+
+/// A time range on one day in the event's time zone, e.g. `2023-09-03 13:00-14:30` or
+/// `mon 9:00-17:00`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlotRange {
+    pub day: RangeDay,
+    /// Minutes after midnight the range starts at.
+    pub start: u32,
+    /// Minutes after midnight the range ends at, up to 24:00.
+    pub end: u32,
+}
+
+impl SlotRange {
+    /// Whether a slot starting at the wall-clock time `start` falls in the range.
+    fn contains(&self, start: NaiveDateTime) -> bool {
+        let on_day = match self.day {
+            RangeDay::Date(date) => start.date() == date,
+            RangeDay::Weekday(weekday) => start.weekday() == weekday,
+        };
+        let minutes = start.hour() * 60 + start.minute();
+
+        on_day && self.start <= minutes && minutes < self.end
+    }
+}
+
+impl std::fmt::Display for SlotRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.day {
+            RangeDay::Date(date) => write!(f, "{}", date)?,
+            RangeDay::Weekday(weekday) => write!(f, "{}", weekday)?,
+        }
+
+        write!(
+            f,
+            " {}:{:02}-{}:{:02}",
+            self.start / 60,
+            self.start % 60,
+            self.end / 60,
+            self.end % 60
+        )
+    }
+}
+
+impl FromStr for SlotRange {
+    type Err = RespondError;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let invalid = || RespondError::InvalidSlotRange {
+            range: range.to_string(),
+        };
+
+        let (day, times) = match range.trim().split_once(char::is_whitespace) {
+            Some(parts) => parts,
+            None => return Err(invalid()),
+        };
+
+        let day = match (day.parse::<NaiveDate>(), day.parse::<Weekday>()) {
+            (Ok(date), _) => RangeDay::Date(date),
+            (_, Ok(weekday)) => RangeDay::Weekday(weekday),
+            _ => return Err(invalid()),
+        };
+
+        let (start, end) = match times.split_once('-') {
+            Some(times) => times,
+            None => return Err(invalid()),
+        };
+
+        match (parse_minutes(start), parse_minutes(end)) {
+            (Some(start), Some(end)) if start < end => Ok(SlotRange { day, start, end }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// `H:MM` or `HH:MM` as minutes after midnight, where `24:00` is the end of the day.
+fn parse_minutes(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+
+    if !(1..=2).contains(&hours.len()) || minutes.len() != 2 {
+        return None;
+    }
+
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+
+    match (hours, minutes) {
+        (0..=23, 0..=59) | (24, 0) => Some(hours * 60 + minutes),
+        _ => None,
+    }
+}
+
+/// Which slots to mark available. Every other slot is marked unavailable.
+#[derive(Debug, PartialEq)]
+pub enum Availability {
+    Ranges(Vec<SlotRange>),
+    /// Wherever the calendar is free.
+    Calendar(Calendar),
+}
+
+/// The participant to sign in as. when2meet adds them to the event if the name is new.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Respondent {
+    pub name: String,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RespondOutcome {
+    /// How many slots are now marked available.
+    pub available: usize,
+    /// How many slots had to be toggled to get there.
+    pub toggled: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignInState {
+    signed_in: bool,
+    alert: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Toggled {
+    toggled: usize,
+    missing: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct SaveState {
+    pending: usize,
+}
+
+/// Signs in to the event at `url` as `respondent` and toggles their slots in the grid until
+/// exactly the ones `availability` picks are marked available. Days of the week slots are
+/// matched to calendar times as in `Event::with_calendar`.
+pub fn respond_to_when2meet(
+    url: &Url,
+    options: &ChromeOptions,
+    respondent: &Respondent,
+    availability: &Availability,
+    week_of: DateTime<Utc>,
+) -> Result<RespondOutcome, RespondError> {
+    let deadline = options.deadline();
+
+    let (_browser, tab) = match open_when2meet(url, options, &deadline) {
+        Ok(opened) => opened,
+        Err(source) => return Err(RespondError::When2Meet(source)),
+    };
+
+    let event = match read_when2meet(&tab, url, &deadline) {
+        Ok(event) => event,
+        Err(source) => return Err(RespondError::When2Meet(source)),
+    };
+
+    let wanted = wanted_slots(event, availability, week_of)?;
+
+    sign_in(&tab, url, respondent, &deadline)?;
+
+    let toggled = toggle_slots(&tab, url, &wanted, &deadline)?;
+
+    wait_until_saved(&tab, url, &deadline)?;
+
+    Ok(RespondOutcome {
+        available: wanted.len(),
+        toggled,
+    })
+}
+
+/// The starts, in unix seconds, of the slots `availability` marks available. Fails if a range
+/// matches no slot, since responding would then clear slots the respondent meant to keep.
+fn wanted_slots(
+    event: Event,
+    availability: &Availability,
+    week_of: DateTime<Utc>,
+) -> Result<Vec<i64>, RespondError> {
+    match availability {
+        Availability::Ranges(ranges) => {
+            let tz = event.calendar_timezone();
+            let starts: Vec<(DateTime<Utc>, NaiveDateTime)> = event
+                .slot_starts()
+                .into_iter()
+                .map(|(start, recurrence)| (start, wall_clock(start, recurrence, tz)))
+                .collect();
+
+            if let Some(range) = ranges.iter().find(|range| {
+                !starts
+                    .iter()
+                    .any(|(_, wall_clock)| range.contains(*wall_clock))
+            }) {
+                return Err(RespondError::UnmatchedSlotRange {
+                    range: range.to_string().into_boxed_str(),
+                });
+            }
+
+            Ok(starts
+                .into_iter()
+                .filter(|(_, wall_clock)| ranges.iter().any(|range| range.contains(*wall_clock)))
+                .map(|(start, _)| start.timestamp())
+                .collect())
+        }
+        Availability::Calendar(calendar) => Ok(event
+            .without_busy(calendar, week_of)
+            .slot_starts()
+            .into_iter()
+            .map(|(start, _)| start.timestamp())
+            .collect()),
+    }
+}
+
+/// A slot's start as a wall-clock time in `tz`. A weekly slot already holds its wall-clock time.
+fn wall_clock(start: DateTime<Utc>, recurrence: Recurrence, tz: Tz) -> NaiveDateTime {
+    match recurrence {
+        Recurrence::Once => start.with_timezone(&tz).naive_local(),
+        Recurrence::Weekly => start.naive_utc(),
+    }
+}
+
+/// Fills in the page's sign in form and waits for when2meet to load the respondent's grid.
+/// Alerts are captured, since one would block the page and it is how a wrong password is shown.
+fn sign_in(
+    tab: &Arc<Tab>,
+    url: &Url,
+    respondent: &Respondent,
+    deadline: &Deadline,
+) -> Result<(), RespondError> {
+    let js_func = format!(
+        r#"
+    (function () {{
+        window.when3meetAlert = null;
+        window.alert = function (message) {{ window.when3meetAlert = String(message); }};
+        document.getElementById("name").value = {};
+        document.getElementById("password").value = {};
+        ProcessLogin();
+        return JSON.stringify({{ signed_in: false, alert: null }});
+    }})();
+    "#,
+        js_string(&respondent.name),
+        js_string(respondent.password.as_deref().unwrap_or("")),
+    );
+
+    evaluate_json::<SignInState>(tab, url, "signing in", &js_func, deadline)?;
+
+    let js_func = r#"
+    (function () {
+        return JSON.stringify({
+            signed_in: typeof UserID !== "undefined" && UserID > 0,
+            alert: window.when3meetAlert,
+        });
+    })();
+    "#;
+
+    let timeout = deadline.step_timeout();
+    let start = Instant::now();
+
+    loop {
+        let state: SignInState = evaluate_json(tab, url, "signing in", js_func, deadline)?;
+
+        if state.signed_in {
+            return Ok(());
+        }
+
+        if let Some(message) = state.alert {
+            return Err(RespondError::SignInRejected {
+                url: url.clone(),
+                name: respondent.name.clone().into_boxed_str(),
+                message: message.into_boxed_str(),
+            });
+        }
+
+        if start.elapsed() >= timeout {
+            return Err(RespondError::SignInTimedOut {
+                url: url.clone(),
+                name: respondent.name.clone().into_boxed_str(),
+                waited: timeout,
+            });
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Clicks every cell of the respondent's grid whose availability differs from `wanted`, the way
+/// the page expects a single cell to be toggled, and returns how many were clicked.
+fn toggle_slots(
+    tab: &Arc<Tab>,
+    url: &Url,
+    wanted: &[i64],
+    deadline: &Deadline,
+) -> Result<usize, RespondError> {
+    let js_func = r#"
+    (function () {
+        const wanted = new Set(WANTED);
+        let toggled = 0;
+        let missing = 0;
+        TimeOfSlot.forEach((time, slot) => {
+            if (AvailableAtSlot[slot].includes(UserID) === wanted.has(time)) {
+                return;
+            }
+            const cell = document.getElementById("YouTime" + time);
+            if (!cell) {
+                missing += 1;
+                return;
+            }
+            cell.dispatchEvent(new MouseEvent("mousedown", { bubbles: true }));
+            cell.dispatchEvent(new MouseEvent("mouseup", { bubbles: true }));
+            toggled += 1;
+        });
+        return JSON.stringify({ toggled: toggled, missing: missing });
+    })();
+    "#
+    .replace("WANTED", &serde_json::Value::from(wanted).to_string());
+
+    let result: Toggled = evaluate_json(tab, url, "toggling slots", &js_func, deadline)?;
+
+    match result.missing {
+        0 => Ok(result.toggled),
+        missing => Err(RespondError::MissingCells {
+            url: url.clone(),
+            missing,
+        }),
+    }
+}
+
+/// Waits for the requests that save each toggled cell to finish, since closing the browser would
+/// cancel them.
+fn wait_until_saved(tab: &Arc<Tab>, url: &Url, deadline: &Deadline) -> Result<(), RespondError> {
+    let js_func = r#"
+    (function () {
+        const prototype = typeof Ajax !== "undefined" ? Ajax.activeRequestCount : 0;
+        const jquery = typeof jQuery !== "undefined" ? jQuery.active : 0;
+        return JSON.stringify({ pending: prototype + jquery });
+    })();
+    "#;
+
+    let timeout = deadline.step_timeout();
+    let start = Instant::now();
+
+    loop {
+        let state: SaveState = evaluate_json(tab, url, "saving", js_func, deadline)?;
+
+        if state.pending == 0 {
+            return Ok(());
+        }
+
+        if start.elapsed() >= timeout {
+            return Err(RespondError::SaveTimedOut {
+                url: url.clone(),
+                waited: timeout,
+            });
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Evaluates `js_func`, which returns a JSON string, and deserializes the result.
+fn evaluate_json<T: DeserializeOwned>(
+    tab: &Arc<Tab>,
+    url: &Url,
+    what: &'static str,
+    js_func: &str,
+    deadline: &Deadline,
+) -> Result<T, RespondError> {
+    let result = match evaluate(tab, url, js_func, deadline) {
+        Ok(result) => result,
+        Err(fetch_error) => return Err(RespondError::Fetch(fetch_error)),
+    };
+
+    let value = match result.value.as_ref().and_then(|value| value.as_str()) {
+        Some(value) => value,
+        None => {
+            return Err(RespondError::UnexpectedResult {
+                url: url.clone(),
+                what,
+                value: format!("{:?}", result.value).into_boxed_str(),
+            })
+        }
+    };
+
+    match serde_json::from_str(value) {
+        Ok(parsed) => Ok(parsed),
+        Err(_) => Err(RespondError::UnexpectedResult {
+            url: url.clone(),
+            what,
+            value: value.to_string().into_boxed_str(),
+        }),
+    }
+}
+
+/// `value` as a JS string literal.
+fn js_string(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fetch_availability::ics::Calendar;
+    use crate::fetch_availability::model::{Event, Person};
+    use crate::manage_event::errors::RespondError;
+    use crate::manage_event::respond::{
+        js_string, wanted_slots, Availability, RangeDay, SlotRange,
+    };
+    use chrono::{DateTime, NaiveDate, Utc, Weekday};
+    use claims::{assert_err, assert_ok};
+
+    fn timestamp(seconds: &str) -> DateTime<Utc> {
+        DateTime::parse_from_str(seconds, "%s")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn event(starts: &[&str], timezone: &str) -> Event {
+        let person = || {
+            vec![Person {
+                id: "94102857".to_string().into_boxed_str(),
+                name: "Muneer".to_string().into_boxed_str(),
+                available: true,
+            }]
+        };
+
+        let mut event = Event::new(
+            starts
+                .iter()
+                .map(|start| (timestamp(start), person()))
+                .collect(),
+        );
+        event.timezone = Some(timezone.parse().unwrap());
+        event
+    }
+
+    #[test]
+    fn test_parse_slot_range() {
+        assert_eq!(
+            "2023-09-03 13:00-14:30".parse::<SlotRange>().unwrap(),
+            SlotRange {
+                day: RangeDay::Date(NaiveDate::from_ymd_opt(2023, 9, 3).unwrap()),
+                start: 13 * 60,
+                end: 14 * 60 + 30,
+            }
+        );
+        assert_eq!(
+            " mon 9:00 - 24:00 ".parse::<SlotRange>().unwrap(),
+            SlotRange {
+                day: RangeDay::Weekday(Weekday::Mon),
+                start: 9 * 60,
+                end: 24 * 60,
+            }
+        );
+        assert_ok!("Sunday 0:00-0:15".parse::<SlotRange>());
+    }
+
+    #[test]
+    fn test_parse_slot_range_invalid() {
+        for range in [
+            "",
+            "2023-09-03",
+            "13:00-14:30",
+            "someday 13:00-14:30",
+            "2023-09-03 13:00",
+            "2023-09-03 14:30-13:00",
+            "2023-09-03 13:00-13:00",
+            "2023-09-03 13:60-14:00",
+            "2023-09-03 13-14",
+            "2023-09-03 13:0-14:00",
+            "2023-09-03 24:00-24:30",
+            "2023-09-03 013:00-14:00",
+        ] {
+            let parsed = range.parse::<SlotRange>();
+
+            assert_err!(&parsed);
+            assert!(matches!(
+                parsed.unwrap_err(),
+                RespondError::InvalidSlotRange { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_wanted_slots_ranges() {
+        // 9:00am to 10:00am on 2023-09-03 in New York, in 15 minute slots.
+        let event = event(
+            &["1693746000", "1693746900", "1693747800", "1693748700"],
+            "America/New_York",
+        );
+        let ranges = vec![
+            "2023-09-03 9:15-9:45".parse().unwrap(),
+            "2023-09-03 9:30-9:45".parse().unwrap(),
+        ];
+
+        assert_eq!(
+            wanted_slots(event, &Availability::Ranges(ranges), Utc::now()).unwrap(),
+            vec![1693746900, 1693747800]
+        );
+    }
+
+    #[test]
+    fn test_wanted_slots_ranges_days_of_the_week() {
+        // Monday 2:00pm to 3:00pm of when2meet's reference week, in 30 minute slots.
+        let event = event(&["396000", "397800"], "America/New_York");
+        let ranges = vec!["mon 14:30-15:00".parse().unwrap()];
+
+        assert_eq!(
+            wanted_slots(event, &Availability::Ranges(ranges), Utc::now()).unwrap(),
+            vec![397800]
+        );
+    }
+
+    #[test]
+    fn test_wanted_slots_unmatched_range() {
+        // A day outside the grid, and a date on a days of the week event.
+        for (starts, matched, unmatched) in [
+            (
+                ["1693746000", "1693746900"],
+                "2023-09-03 9:00-10:00",
+                "2023-09-04 9:00-10:00",
+            ),
+            (
+                ["396000", "397800"],
+                "mon 14:00-15:00",
+                "2023-09-04 14:00-15:00",
+            ),
+        ] {
+            let ranges = vec![matched.parse().unwrap(), unmatched.parse().unwrap()];
+
+            let wanted = wanted_slots(
+                event(&starts, "America/New_York"),
+                &Availability::Ranges(ranges),
+                Utc::now(),
+            );
+
+            assert_err!(&wanted);
+
+            assert_eq!(
+                wanted.unwrap_err().to_string(),
+                format!(
+                    "The slot range '{}' matches no slot of the event; check the dates, weekdays and time zone",
+                    unmatched
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_wanted_slots_calendar() {
+        let calendar = Calendar::parse(
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:20230903T131500Z\nDTEND:20230903T134500Z\nEND:VEVENT\nEND:VCALENDAR\n",
+        )
+        .unwrap();
+        let event = event(
+            &["1693746000", "1693746900", "1693747800", "1693748700"],
+            "America/New_York",
+        );
+
+        assert_eq!(
+            wanted_slots(event, &Availability::Calendar(calendar), Utc::now()).unwrap(),
+            vec![1693746000, 1693748700]
+        );
+    }
+
+    #[test]
+    fn test_js_string() {
+        assert_eq!(js_string("O'Brien \"Bri\""), r#""O'Brien \"Bri\"""#);
+    }
+}
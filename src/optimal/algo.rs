@@ -1,54 +1,102 @@
 use crate::fetch_availability::model::Slot;
+use crate::optimal::errors::FindOptError;
+use std::collections::HashSet;
 
 pub fn find_opt<'a>(
     slots: &'a [Slot],
     required_people: &[String],
     flexible_naming: &bool,
-) -> Vec<&'a Slot> {
+) -> Result<Vec<&'a Slot>, FindOptError> {
     if !required_people.is_empty() {
-        slots
+        let required_ids = resolve_required_people(slots, required_people, flexible_naming)?;
+
+        Ok(slots
             .iter()
             .filter(|slot| {
-                required_people.iter().all(|required_name| {
-                    slot.people.iter().any(|slot_person| {
-                        let name_match = if *flexible_naming {
-                            slot_person
-                                .name
-                                .to_lowercase()
-                                .contains(&required_name.to_lowercase())
-                        } else {
-                            &*slot_person.name == required_name
-                        };
-                        name_match && slot_person.available
-                    })
+                required_ids.iter().all(|id| {
+                    slot.people
+                        .iter()
+                        .any(|slot_person| slot_person.available && Some(&*slot_person.id) == *id)
                 })
             })
-            .collect()
+            .collect())
     } else {
-        let max_available_count = slots
-            .iter()
-            .map(|slot| slot.people.iter().filter(|person| person.available).count())
-            .max()
-            .unwrap_or(0);
+        let max_available_count = slots.iter().map(available_count).max().unwrap_or(0);
 
         if max_available_count == 0 {
-            return vec![];
+            return Ok(vec![]);
         }
 
-        slots
+        Ok(slots
             .iter()
-            .filter(|slot| {
-                slot.people.iter().filter(|person| person.available).count() == max_available_count
-            })
-            .collect()
+            .filter(|slot| available_count(slot) == max_available_count)
+            .collect())
     }
 }
 
+/// Resolves each required person to the ID of the participant they refer to, matching either the
+/// participant's ID exactly or else their name, or `None` if nobody matches. Fails if a name
+/// matches several people, since they may be available at different times.
+fn resolve_required_people<'a>(
+    slots: &'a [Slot],
+    required_people: &[String],
+    flexible_naming: &bool,
+) -> Result<Vec<Option<&'a str>>, FindOptError> {
+    let people = || slots.iter().flat_map(|slot| &slot.people);
+
+    required_people
+        .iter()
+        .map(|required_name| {
+            if let Some(person) = people().find(|person| &*person.id == required_name) {
+                return Ok(Some(&*person.id));
+            }
+
+            let mut ids: Vec<&str> = Vec::new();
+            for person in people() {
+                let name_match = if *flexible_naming {
+                    person
+                        .name
+                        .to_lowercase()
+                        .contains(&required_name.to_lowercase())
+                } else {
+                    &*person.name == required_name
+                };
+
+                if name_match && !ids.contains(&&*person.id) {
+                    ids.push(&person.id);
+                }
+            }
+
+            match ids.as_slice() {
+                [] => Ok(None),
+                [id] => Ok(Some(*id)),
+                _ => Err(FindOptError::AmbiguousPerson {
+                    name: required_name.clone(),
+                    ids: ids
+                        .into_iter()
+                        .map(|id| id.to_string().into_boxed_str())
+                        .collect(),
+                }),
+            }
+        })
+        .collect()
+}
+
+fn available_count(slot: &Slot) -> usize {
+    slot.people
+        .iter()
+        .filter(|person| person.available)
+        .map(|person| &person.id)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::fetch_availability::model::{Person, Slot};
+    use crate::fetch_availability::model::{Event, Person, Recurrence, Slot};
     use crate::optimal::algo::find_opt;
-    use chrono::{DateTime, Utc};
+    use crate::optimal::errors::FindOptError;
+    use chrono::{DateTime, Duration, Utc, Weekday};
 
     #[test]
     fn test_find_opt() {
@@ -57,16 +105,20 @@ mod tests {
                 DateTime::parse_from_str("1693746000", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -76,16 +128,20 @@ mod tests {
                 DateTime::parse_from_str("1693746900", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -95,16 +151,20 @@ mod tests {
                 DateTime::parse_from_str("1693747800", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -116,7 +176,7 @@ mod tests {
 
         let flexible_naming = false;
 
-        let opt = find_opt(&slots, &required_people, &flexible_naming);
+        let opt = find_opt(&slots, &required_people, &flexible_naming).unwrap();
 
         assert_eq!(opt.len(), 1);
 
@@ -146,16 +206,20 @@ mod tests {
                 DateTime::parse_from_str("1693746000", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -165,16 +229,20 @@ mod tests {
                 DateTime::parse_from_str("1693746900", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -184,16 +252,20 @@ mod tests {
                 DateTime::parse_from_str("1693747800", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -205,7 +277,7 @@ mod tests {
 
         let flexible_naming = false;
 
-        let opt = find_opt(&slots, &required_people, &flexible_naming);
+        let opt = find_opt(&slots, &required_people, &flexible_naming).unwrap();
 
         assert!(opt.is_empty());
     }
@@ -217,16 +289,20 @@ mod tests {
                 DateTime::parse_from_str("1693746000", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: true,
                     },
@@ -236,16 +312,20 @@ mod tests {
                 DateTime::parse_from_str("1693746900", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: true,
                     },
@@ -255,16 +335,20 @@ mod tests {
                 DateTime::parse_from_str("1693747800", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: true,
                     },
@@ -276,7 +360,7 @@ mod tests {
 
         let flexible_naming = false;
 
-        let opt = find_opt(&slots, &required_people, &flexible_naming);
+        let opt = find_opt(&slots, &required_people, &flexible_naming).unwrap();
 
         let slots: Vec<&Slot> = slots.iter().collect();
 
@@ -292,16 +376,20 @@ mod tests {
                 DateTime::parse_from_str("1693746000", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -311,16 +399,20 @@ mod tests {
                 DateTime::parse_from_str("1693746900", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -330,16 +422,20 @@ mod tests {
                 DateTime::parse_from_str("1693747800", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -355,7 +451,7 @@ mod tests {
 
         let flexible_naming = false;
 
-        let opt = find_opt(&slots, &required_people, &flexible_naming);
+        let opt = find_opt(&slots, &required_people, &flexible_naming).unwrap();
 
         assert!(opt.is_empty());
     }
@@ -367,16 +463,20 @@ mod tests {
                 DateTime::parse_from_str("1693746000", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -386,16 +486,20 @@ mod tests {
                 DateTime::parse_from_str("1693746900", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -405,16 +509,20 @@ mod tests {
                 DateTime::parse_from_str("1693747800", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -424,16 +532,20 @@ mod tests {
                 DateTime::parse_from_str("1693748700", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: true,
                     },
@@ -443,7 +555,7 @@ mod tests {
 
         let flexible_naming = false;
 
-        let opt = find_opt(&slots, &[], &flexible_naming);
+        let opt = find_opt(&slots, &[], &flexible_naming).unwrap();
 
         print!("{:?}", opt);
 
@@ -473,16 +585,20 @@ mod tests {
                 DateTime::parse_from_str("1693743800", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: true,
                     },
@@ -492,16 +608,20 @@ mod tests {
                 DateTime::parse_from_str("1693746000", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -511,16 +631,20 @@ mod tests {
                 DateTime::parse_from_str("1693746900", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -530,16 +654,20 @@ mod tests {
                 DateTime::parse_from_str("1693747800", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -549,16 +677,20 @@ mod tests {
                 DateTime::parse_from_str("1693748700", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: "Muneer".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: "Brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: true,
                     },
@@ -568,7 +700,7 @@ mod tests {
 
         let flexible_naming = false;
 
-        let opt = find_opt(&slots, &[], &flexible_naming);
+        let opt = find_opt(&slots, &[], &flexible_naming).unwrap();
 
         print!("{:?}", opt);
 
@@ -599,16 +731,20 @@ mod tests {
                 DateTime::parse_from_str("1693746000", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: " mUnEeR lAlJi ".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: " reicher, brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -618,16 +754,20 @@ mod tests {
                 DateTime::parse_from_str("1693746900", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: " mUnEeR lAlJi ".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: " reicher, brian".to_string().into_boxed_str(),
                         available: false,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -637,16 +777,20 @@ mod tests {
                 DateTime::parse_from_str("1693747800", "%s")
                     .unwrap()
                     .with_timezone(&Utc),
+                Duration::minutes(15),
                 vec![
                     Person {
+                        id: "94102857".to_string().into_boxed_str(),
                         name: " mUnEeR lAlJi ".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102860".to_string().into_boxed_str(),
                         name: " reicher, brian".to_string().into_boxed_str(),
                         available: true,
                     },
                     Person {
+                        id: "94102911".to_string().into_boxed_str(),
                         name: "Garrett".to_string().into_boxed_str(),
                         available: false,
                     },
@@ -658,7 +802,7 @@ mod tests {
 
         let flexible_naming = true;
 
-        let opt = find_opt(&slots, &required_people, &flexible_naming);
+        let opt = find_opt(&slots, &required_people, &flexible_naming).unwrap();
 
         assert_eq!(opt.len(), 1);
 
@@ -680,4 +824,111 @@ mod tests {
                 .available
         );
     }
+
+    #[test]
+    fn test_find_opt_same_name_different_ids() {
+        let slots = vec![
+            Slot::new(
+                DateTime::parse_from_str("1693746000", "%s")
+                    .unwrap()
+                    .with_timezone(&Utc),
+                Duration::minutes(15),
+                vec![
+                    Person {
+                        id: "94102857".to_string().into_boxed_str(),
+                        name: "Alex".to_string().into_boxed_str(),
+                        available: true,
+                    },
+                    Person {
+                        id: "94102860".to_string().into_boxed_str(),
+                        name: "Alex".to_string().into_boxed_str(),
+                        available: false,
+                    },
+                ],
+            ),
+            Slot::new(
+                DateTime::parse_from_str("1693746900", "%s")
+                    .unwrap()
+                    .with_timezone(&Utc),
+                Duration::minutes(15),
+                vec![
+                    Person {
+                        id: "94102857".to_string().into_boxed_str(),
+                        name: "Alex".to_string().into_boxed_str(),
+                        available: false,
+                    },
+                    Person {
+                        id: "94102860".to_string().into_boxed_str(),
+                        name: "Alex".to_string().into_boxed_str(),
+                        available: true,
+                    },
+                ],
+            ),
+        ];
+
+        let flexible_naming = false;
+
+        let opt = find_opt(&slots, &["94102860".to_string()], &flexible_naming).unwrap();
+
+        assert_eq!(opt, vec![&slots[1]]);
+
+        let opt = find_opt(&slots, &["Alex".to_string()], &flexible_naming);
+
+        assert_eq!(
+            opt.unwrap_err(),
+            FindOptError::AmbiguousPerson {
+                name: "Alex".to_string(),
+                ids: vec![
+                    "94102857".to_string().into_boxed_str(),
+                    "94102860".to_string().into_boxed_str()
+                ],
+            }
+        );
+
+        let opt = find_opt(&slots, &[], &flexible_naming).unwrap();
+
+        assert_eq!(opt.len(), 2);
+    }
+
+    #[test]
+    fn test_find_opt_days_of_the_week() {
+        let people = |muneer, brian| {
+            vec![
+                Person {
+                    id: "94102857".to_string().into_boxed_str(),
+                    name: "Muneer".to_string().into_boxed_str(),
+                    available: muneer,
+                },
+                Person {
+                    id: "94102860".to_string().into_boxed_str(),
+                    name: "Brian".to_string().into_boxed_str(),
+                    available: brian,
+                },
+            ]
+        };
+
+        // Monday and Tuesday 2:00pm of when2meet's reference week.
+        let event = Event::new(vec![
+            (
+                DateTime::parse_from_str("396000", "%s")
+                    .unwrap()
+                    .with_timezone(&Utc),
+                people(true, false),
+            ),
+            (
+                DateTime::parse_from_str("482400", "%s")
+                    .unwrap()
+                    .with_timezone(&Utc),
+                people(true, true),
+            ),
+        ]);
+
+        let flexible_naming = false;
+
+        let opt = find_opt(&event.slots, &[], &flexible_naming).unwrap();
+
+        assert_eq!(opt, vec![&event.slots[1]]);
+        assert_eq!(opt[0].recurrence, Recurrence::Weekly);
+        assert_eq!(opt[0].weekday(), Weekday::Tue);
+    }
 }
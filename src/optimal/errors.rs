@@ -0,0 +1,7 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FindOptError {
+    #[error("'{name}' matches more than one person, with the IDs {}; pass the ID of the one you mean to --required-people instead", ids.join(", "))]
+    AmbiguousPerson { name: String, ids: Vec<Box<str>> },
+}
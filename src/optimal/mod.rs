@@ -1,3 +1,4 @@
 pub mod algo;
+pub mod errors;
 
 pub use algo::find_opt;
@@ -1,3 +1,3 @@
 pub mod write;
 
-pub use write::write_slots;
+pub use write::{write_event, write_slots};
@@ -2,7 +2,18 @@ use std::io::Write;
 
 use anyhow::{Context, Result};
 
-use crate::fetch_availability::Slot;
+use crate::fetch_availability::{Event, Slot};
+
+pub fn write_event<W>(event: &Event, slots: &[&Slot], writer: &mut W) -> Result<()>
+where
+    W: Write,
+{
+    if let Some(title) = &event.title {
+        writeln!(writer, "Event: {}\n", title).context("Failed to write to output")?;
+    }
+
+    write_slots(slots, writer)
+}
 
 pub fn write_slots<W>(slots: &[&Slot], writer: &mut W) -> Result<()>
 where
@@ -0,0 +1,479 @@
+use std::process::Command;
+
+const FIXTURE_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/when2meet_event.html"
+);
+
+const DAYS_OF_WEEK_FIXTURE_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/when2meet_days_of_week.html"
+);
+
+fn when3meet(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_when3meet"))
+        .args(args)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_from_html_all_people() {
+    let output = when3meet(&["--from-html", FIXTURE_PATH]);
+
+    assert!(output.starts_with("Event: Team Sync\n\nTimestamp: Sunday 01:45pm - 02:00pm"));
+    assert!(output.ends_with("Available People:\n- Muneer\n- Brian\n- Garrett\n"));
+}
+
+#[test]
+fn test_from_html_required_people() {
+    let output = when3meet(&["--from-html", FIXTURE_PATH, "-r", "Muneer", "Brian"]);
+
+    let slots: Vec<&str> = output.split("\n\n").skip(1).collect();
+
+    assert_eq!(slots.len(), 2);
+    assert!(slots[0].starts_with("Timestamp: Sunday 01:30pm - 01:45pm"));
+    assert!(slots[0].ends_with("Unavailable People:\n- Garrett"));
+    assert!(slots[1].starts_with("Timestamp: Sunday 01:45pm - 02:00pm"));
+}
+
+#[test]
+fn test_from_html_output_file() {
+    let path = std::env::temp_dir().join("when3meet_test_from_html_output_file.txt");
+
+    let output = when3meet(&["--from-html", FIXTURE_PATH, "-o", path.to_str().unwrap()]);
+
+    assert!(output.is_empty());
+
+    let written = std::fs::read_to_string(&path).unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(written.ends_with("Available People:\n- Muneer\n- Brian\n- Garrett\n"));
+}
+
+#[test]
+fn test_requires_an_input() {
+    let output = Command::new(env!("CARGO_BIN_EXE_when3meet"))
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_from_snapshot_matches_from_html() {
+    let snapshot = when3meet::fetch_availability::snapshot_when2meet_html(
+        &std::fs::read_to_string(FIXTURE_PATH).unwrap(),
+    )
+    .unwrap();
+    let path = std::env::temp_dir().join("when3meet_test_from_snapshot.json");
+
+    std::fs::write(&path, snapshot.to_json().unwrap()).unwrap();
+
+    let from_snapshot = when3meet(&["--from-snapshot", path.to_str().unwrap()]);
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(from_snapshot, when3meet(&["--from-html", FIXTURE_PATH]));
+}
+
+#[test]
+fn test_snapshot_requires_url() {
+    let output = Command::new(env!("CARGO_BIN_EXE_when3meet"))
+        .arg("snapshot")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--when2meet-url"));
+}
+
+#[test]
+fn test_from_html_days_of_the_week() {
+    let output = when3meet(&[
+        "--from-html",
+        DAYS_OF_WEEK_FIXTURE_PATH,
+        "-r",
+        "Muneer",
+        "Brian",
+    ]);
+
+    assert_eq!(
+        output,
+        "Event: Weekly Standup\n\n\
+         Timestamp: Mondays 03:00pm - 03:30pm\n\
+         Available People:\n- Muneer\n- Brian\n\
+         Unavailable People:\n- Garrett\n\n\
+         Timestamp: Tuesdays 02:00pm - 02:30pm\n\
+         Available People:\n- Muneer\n- Brian\n- Garrett\n"
+    );
+}
+
+#[test]
+fn test_error_reports_cause_chain() {
+    let output = Command::new(env!("CARGO_BIN_EXE_when3meet"))
+        .args(["--from-html", "does/not/exist.html"])
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("Failed to read file does/not/exist.html"));
+    assert!(stderr.contains("Caused by:"));
+}
+
+#[test]
+fn test_rejects_non_event_url() {
+    let output = Command::new(env!("CARGO_BIN_EXE_when3meet"))
+        .args(["--when2meet-url", "https://www.when2meet.com/"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("does not point to an event"));
+}
+
+#[test]
+fn test_from_html_not_an_event() {
+    let path = std::env::temp_dir().join("when3meet_test_not_an_event.html");
+
+    std::fs::write(&path, "<html><body>When2meet</body></html>").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_when3meet"))
+        .args(["--from-html", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is not a when2meet event page"));
+}
+
+#[test]
+fn test_from_html_no_responses_yet() {
+    let path = std::env::temp_dir().join("when3meet_test_no_responses.html");
+
+    std::fs::write(
+        &path,
+        "<script>var PeopleNames = new Array();var AvailableAtSlot = new Array();TimeOfSlot[0]=1693746000;TimeOfSlot[1]=1693746900;</script>",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_when3meet"))
+        .args(["--from-html", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No responses yet"));
+}
+
+#[test]
+fn test_from_html_no_responses_yet_with_ics() {
+    let path = std::env::temp_dir().join("when3meet_test_no_responses_with_ics.html");
+
+    std::fs::write(
+        &path,
+        "<script>var PeopleNames = new Array();var AvailableAtSlot = new Array();TimeOfSlot[0]=1693746000;TimeOfSlot[1]=1693746900;</script>",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_when3meet"))
+        .args([
+            "--from-html",
+            path.to_str().unwrap(),
+            "--ics",
+            concat!(
+                "Dana=",
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/calendar.ics"
+            ),
+        ])
+        .output()
+        .unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+
+    // Only the calendar's owner would be in the grid, which is no response to the event.
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No responses yet"));
+}
+
+#[test]
+fn test_invalid_config_file() {
+    let path = std::env::temp_dir().join("when3meet_test_invalid_config.toml");
+
+    std::fs::write(&path, "[chrome]\nsandbox = false\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_when3meet"))
+        .args([
+            "--from-html",
+            FIXTURE_PATH,
+            "--config",
+            path.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to parse config file"));
+}
+
+#[test]
+fn test_offline_without_cached_copy() {
+    let cache_dir = std::env::temp_dir().join("when3meet_test_offline_cache");
+    let config_path = std::env::temp_dir().join("when3meet_test_offline_config.toml");
+
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    std::fs::write(
+        &config_path,
+        format!("[cache]\ndir = {:?}\n", cache_dir.to_str().unwrap()),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_when3meet"))
+        .args([
+            "--when2meet-url",
+            "https://www.when2meet.com/?21240105-aBcDe",
+            "--offline",
+            "--config",
+            config_path.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+
+    std::fs::remove_file(&config_path).unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No cached copy of"));
+}
+
+#[test]
+fn test_from_html_with_aliases() {
+    let path = std::env::temp_dir().join("when3meet_test_aliases.toml");
+
+    std::fs::write(&path, "\"Garrett Ladley\" = [\"garrett\", \"GL\"]\n").unwrap();
+
+    let output = when3meet(&[
+        "--from-html",
+        FIXTURE_PATH,
+        "--aliases",
+        path.to_str().unwrap(),
+        "-r",
+        "Garrett Ladley",
+    ]);
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.contains("- Garrett Ladley\n"));
+    assert!(!output.contains("- Garrett\n"));
+}
+
+#[test]
+fn test_from_html_required_alias() {
+    let path = std::env::temp_dir().join("when3meet_test_required_alias.toml");
+
+    std::fs::write(&path, "\"Garrett Ladley\" = [\"garrett\", \"GL\"]\n").unwrap();
+
+    let output = |required: &str| {
+        when3meet(&[
+            "--from-html",
+            FIXTURE_PATH,
+            "--aliases",
+            path.to_str().unwrap(),
+            "-r",
+            required,
+        ])
+    };
+
+    let canonical = output("Garrett Ladley");
+    let alias = output("GL");
+    let lowercase = output("garrett");
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(canonical.contains("- Garrett Ladley\n"));
+    assert_eq!(alias, canonical);
+    assert_eq!(lowercase, canonical);
+}
+
+#[test]
+fn test_from_csv_matches_from_html() {
+    let from_csv = when3meet(&[
+        "--from-csv",
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/availability.csv"
+        ),
+    ]);
+    let from_html = when3meet(&["--from-html", FIXTURE_PATH]);
+
+    assert_eq!(
+        from_csv,
+        from_html.strip_prefix("Event: Team Sync\n\n").unwrap()
+    );
+}
+
+#[test]
+fn test_from_json_matches_from_html() {
+    let output = when3meet(&[
+        "--from-json",
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/availability.json"
+        ),
+    ]);
+
+    assert_eq!(output, when3meet(&["--from-html", FIXTURE_PATH]));
+}
+
+#[test]
+fn test_from_html_with_ics() {
+    let output = when3meet(&[
+        "--from-html",
+        FIXTURE_PATH,
+        "--ics",
+        concat!(
+            "Dana=",
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/calendar.ics"
+        ),
+        "-r",
+        "Dana",
+        "Muneer",
+    ]);
+
+    let slots: Vec<&str> = output.split("\n\n").skip(1).collect();
+
+    // Dana's calendar is busy for every other slot Muneer is available in.
+    assert_eq!(slots.len(), 1);
+    assert!(slots[0].starts_with("Timestamp: Sunday 01:30pm - 01:45pm"));
+    assert!(slots[0].contains("- Dana\n"));
+}
+
+#[test]
+fn test_from_html_with_caldav_veto() {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let calendar = format!("Dana=http://{}/dana/calendar/", server.server_addr());
+
+    let handle = std::thread::spawn(move || {
+        let request = server.recv().unwrap();
+        let free_busy = "BEGIN:VCALENDAR\r\nBEGIN:VFREEBUSY\r\nFREEBUSY:20230903T134500Z/PT15M\r\nEND:VFREEBUSY\r\nEND:VCALENDAR\r\n";
+        request
+            .respond(tiny_http::Response::from_string(free_busy))
+            .unwrap();
+    });
+
+    let output = when3meet(&[
+        "--from-html",
+        FIXTURE_PATH,
+        "--caldav",
+        &calendar,
+        "--caldav-veto",
+    ]);
+
+    handle.join().unwrap();
+
+    // The slot everyone is available in is vetoed, which leaves the one with Muneer and Brian.
+    assert!(output.starts_with("Event: Team Sync\n\nTimestamp: Sunday 01:30pm - 01:45pm"));
+    assert!(!output.contains("01:45pm - 02:00pm"));
+}
+
+#[test]
+fn test_crabfit_matches_from_html() {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let config_path = std::env::temp_dir().join("when3meet_test_crabfit_config.toml");
+
+    std::fs::write(
+        &config_path,
+        format!("[crabfit]\napi_url = \"http://{}\"\n", server.server_addr()),
+    )
+    .unwrap();
+
+    let handle = std::thread::spawn(move || {
+        for _ in 0..2 {
+            let request = server.recv().unwrap();
+            let body = match request.url() {
+                "/event/team-sync-123456" => {
+                    r#"{"name": "Team Sync", "timezone": "America/New_York", "times": ["1300-03092023", "1315-03092023", "1330-03092023", "1345-03092023"]}"#
+                }
+                _ => {
+                    r#"[{"name": "Muneer", "availability": ["1315-03092023", "1330-03092023", "1345-03092023"]}, {"name": "Brian", "availability": ["1330-03092023", "1345-03092023"]}, {"name": "Garrett", "availability": ["1345-03092023"]}]"#
+                }
+            };
+            request
+                .respond(tiny_http::Response::from_string(body))
+                .unwrap();
+        }
+    });
+
+    let output = when3meet(&[
+        "--crabfit",
+        "https://crab.fit/team-sync-123456",
+        "--config",
+        config_path.to_str().unwrap(),
+    ]);
+
+    handle.join().unwrap();
+    std::fs::remove_file(&config_path).unwrap();
+
+    assert_eq!(output, when3meet(&["--from-html", FIXTURE_PATH]));
+}
+
+#[test]
+fn test_create_prints_event_url() {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", server.server_addr());
+
+    let handle = std::thread::spawn(move || {
+        let mut request = server.recv().unwrap();
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body).unwrap();
+        request
+            .respond(tiny_http::Response::from_string(
+                "<body onload=\"window.location='./?21240105-aBcDe'\"></body>",
+            ))
+            .unwrap();
+        body
+    });
+
+    let output = when3meet(&[
+        "create",
+        "--title",
+        "Team Sync",
+        "--weekdays",
+        "mon",
+        "wed",
+        "--from",
+        "10",
+        "--to",
+        "12",
+        "--timezone",
+        "America/New_York",
+        "--base-url",
+        &base_url,
+    ]);
+
+    let body = handle.join().unwrap();
+
+    assert_eq!(output, format!("{}/?21240105-aBcDe\n", base_url));
+    assert!(body
+        .contains("DateTypes=DaysOfTheWeek&PossibleDates=1%7C3&NoEarlierThan=10&NoLaterThan=12"));
+}